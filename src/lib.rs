@@ -46,11 +46,14 @@
 //! stable, and I may add/change any functionality I do not yet consider
 //! complete.
 use std::borrow::Borrow;
+use std::cmp::Reverse;
 use std::collections::hash_map::{DefaultHasher, RandomState};
-use std::collections::{hash_map, hash_set, HashMap, HashSet};
+use std::collections::{hash_map, hash_set, BTreeSet, BinaryHeap, HashMap, HashSet};
 use std::hash::{BuildHasher, Hash, Hasher};
-use std::ops::{Deref, Index};
+use std::ops::{Deref, Index, RangeBounds, RangeInclusive};
 use std::panic::UnwindSafe;
+#[cfg(feature = "intern")]
+use std::sync::Arc;
 
 /// The `Freeze` trait is a helper trait to make freezing maps and sets more
 /// natural.
@@ -61,236 +64,3477 @@ pub trait Freeze {
     fn freeze(self) -> Self::Frozen;
 }
 
+/// The `FreezeShrunk` trait extends [`Freeze`] with a way to trim spare
+/// capacity while freezing.
+///
+/// ```rust
+/// use std::collections::HashSet;
+///
+/// use frozenset::FreezeShrunk;
+///
+/// let mut set = HashSet::with_capacity(1024);
+/// set.insert(1);
+/// let frozen = set.freeze_shrunk();
+/// assert!(frozen.capacity() < 1024);
+/// ```
+pub trait FreezeShrunk: Freeze {
+    /// Freeze this object, first trimming any spare capacity.
+    ///
+    /// A collection built up incrementally usually has more capacity than it
+    /// needs, and since a frozen collection can never grow that capacity is
+    /// wasted. Trimming costs a reallocation (and a rehash) up front, so it is
+    /// worthwhile for collections that will be kept alive for a long time -
+    /// for example, as keys in a long-lived cache - but not for short-lived
+    /// temporaries, where plain [`freeze`](Freeze::freeze) is cheaper.
+    fn freeze_shrunk(self) -> Self::Frozen;
+}
+
+/// The `BitmaskElement` trait is implemented by types with at most 64 values,
+/// each of which is assigned its own bit, so that sets of them can be packed
+/// into a `u64` with [`FrozenSet::to_bitmask`].
+///
+/// There is no derive macro for this trait; for a fieldless enum, it can be
+/// implemented by casting to get the bit index, and matching to get the value
+/// back:
+/// ```rust
+/// use frozenset::{BitmaskElement, FrozenSet};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// enum Colour {
+///     Red,
+///     Green,
+///     Blue,
+/// }
+/// impl BitmaskElement for Colour {
+///     fn bit_index(&self) -> u32 {
+///         *self as u32
+///     }
+///
+///     fn from_bit_index(index: u32) -> Option<Self> {
+///         match index {
+///             0 => Some(Colour::Red),
+///             1 => Some(Colour::Green),
+///             2 => Some(Colour::Blue),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let warm: FrozenSet<Colour> = [Colour::Red].into();
+/// let cool: FrozenSet<Colour> = [Colour::Green, Colour::Blue].into();
+/// assert_eq!(warm.to_bitmask(), 0b001);
+/// let mask = warm.to_bitmask() | cool.to_bitmask();
+/// let all: FrozenSet<Colour> = FrozenSet::from_bitmask(mask);
+/// assert_eq!(all, [Colour::Red, Colour::Green, Colour::Blue].into());
+/// ```
+pub trait BitmaskElement: Sized {
+    /// Get the index, in `0..64`, of the bit representing this value.
+    fn bit_index(&self) -> u32;
+
+    /// Get the value represented by the bit with the given index, if there is
+    /// one.
+    fn from_bit_index(index: u32) -> Option<Self>;
+}
+
+/// The `Successor` trait is implemented by types where every value (except
+/// the largest) has a next value, so that runs of consecutive elements can be
+/// collapsed into ranges with [`FrozenSet::to_ranges`].
+///
+/// It is implemented for all of the primitive integer types.
+pub trait Successor: Sized {
+    /// Get the value immediately after this one, or `None` if this is the
+    /// largest value.
+    fn successor(&self) -> Option<Self>;
+}
+
+macro_rules! impl_successor {
+    ($($t:ty),*) => {
+        $(
+            impl Successor for $t {
+                fn successor(&self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+            }
+        )*
+    };
+}
+impl_successor!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// The `CanonicalBytes` trait is implemented by types with a single,
+/// platform-independent byte encoding, so that maps of them can be encoded
+/// deterministically with [`FrozenMap::to_canonical_bytes`].
+///
+/// It is implemented for the primitive integer types (in big-endian order,
+/// with `usize` and `isize` widened to 64 bits), `bool`, `char`, and `String`
+/// (as UTF-8).
+pub trait CanonicalBytes: Sized {
+    /// Append the encoding of this value to `out`.
+    fn write_canonical(&self, out: &mut Vec<u8>);
+
+    /// Decode a value from the whole of `bytes`, returning `None` if `bytes`
+    /// is not a valid encoding.
+    fn read_canonical(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_canonical_bytes {
+    ($($t:ty),*) => {
+        $(
+            impl CanonicalBytes for $t {
+                fn write_canonical(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_be_bytes());
+                }
+
+                fn read_canonical(bytes: &[u8]) -> Option<Self> {
+                    Some(Self::from_be_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+impl_canonical_bytes!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl CanonicalBytes for usize {
+    fn write_canonical(&self, out: &mut Vec<u8>) {
+        (*self as u64).write_canonical(out);
+    }
+
+    fn read_canonical(bytes: &[u8]) -> Option<Self> {
+        u64::read_canonical(bytes)?.try_into().ok()
+    }
+}
+impl CanonicalBytes for isize {
+    fn write_canonical(&self, out: &mut Vec<u8>) {
+        (*self as i64).write_canonical(out);
+    }
+
+    fn read_canonical(bytes: &[u8]) -> Option<Self> {
+        i64::read_canonical(bytes)?.try_into().ok()
+    }
+}
+impl CanonicalBytes for bool {
+    fn write_canonical(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+
+    fn read_canonical(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+}
+impl CanonicalBytes for char {
+    fn write_canonical(&self, out: &mut Vec<u8>) {
+        u32::from(*self).write_canonical(out);
+    }
+
+    fn read_canonical(bytes: &[u8]) -> Option<Self> {
+        char::from_u32(u32::read_canonical(bytes)?)
+    }
+}
+impl CanonicalBytes for String {
+    fn write_canonical(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn read_canonical(bytes: &[u8]) -> Option<Self> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+/// Append `value` to `out`, preceded by the length of its encoding.
+fn write_length_prefixed<T: CanonicalBytes>(value: &T, out: &mut Vec<u8>) {
+    let start = out.len();
+    out.extend_from_slice(&[0; 8]);
+    value.write_canonical(out);
+    let len = (out.len() - start - 8) as u64;
+    out[start..start + 8].copy_from_slice(&len.to_be_bytes());
+}
+
+/// Split a length-prefixed encoding off the front of `bytes`, and decode it.
+fn read_length_prefixed<T: CanonicalBytes>(bytes: &mut &[u8]) -> Option<T> {
+    let len = u64::read_canonical(bytes.get(..8)?)?;
+    let rest = &bytes[8..];
+    let len = usize::try_from(len).ok().filter(|&len| len <= rest.len())?;
+    let (value, rest) = rest.split_at(len);
+    *bytes = rest;
+    T::read_canonical(value)
+}
+
+/// Hash a single element of a set, for combining into the hash of the whole
+/// set.
+fn element_hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Combine the hashes of a collection's elements into the value hashed by
+/// `FrozenSet`. Every other type which hashes the same as a `FrozenSet` must
+/// go through this function.
+fn combined_element_hash<'a, T: Hash + 'a>(
+    elements: impl IntoIterator<Item = &'a T>,
+) -> u64 {
+    // A fairly simple hash algorithm. Probably not *great* from a
+    // collision-avoidance perspective, but it's fast, simple, and
+    // consistent.
+
+    // The overall hash is the XOR of the hashes of all the elements, which
+    // will be consistent no matter the iteration order.
+    let mut overall_hash = 0;
+    for v in elements {
+        overall_hash ^= element_hash(v);
+    }
+    overall_hash
+}
+
 impl<K, V, S> Freeze for HashMap<K, V, S> {
     type Frozen = FrozenMap<K, V, S>;
 
-    fn freeze(self) -> Self::Frozen {
-        FrozenMap {
-            map: self,
-        }
+    fn freeze(self) -> Self::Frozen {
+        FrozenMap {
+            map: self,
+        }
+    }
+}
+impl<T, S> Freeze for HashSet<T, S> {
+    type Frozen = FrozenSet<T, S>;
+
+    fn freeze(self) -> Self::Frozen {
+        FrozenSet {
+            set: self,
+        }
+    }
+}
+impl<K: Eq + Hash, V, S: BuildHasher> FreezeShrunk for HashMap<K, V, S> {
+    fn freeze_shrunk(mut self) -> Self::Frozen {
+        self.shrink_to_fit();
+        self.freeze()
+    }
+}
+impl<T: Eq + Hash, S: BuildHasher> FreezeShrunk for HashSet<T, S> {
+    fn freeze_shrunk(mut self) -> Self::Frozen {
+        self.shrink_to_fit();
+        self.freeze()
+    }
+}
+
+/// A `FrozenMap` is a wrapper around a [`HashMap`] that implements [`Hash`].
+///
+/// It is a logic error to mutate any element of the map (via internal
+/// mutability) after it has been frozen.
+///
+/// For convenience, `FrozenMap` implements all of [`HashMap`]'s traits, and
+/// will [`Deref`] to [`HashMap`], so you can use it as a drop-in replacement
+/// for an `&HashMap`.
+#[derive(Debug, Clone)]
+pub struct FrozenMap<K, V, S = RandomState> {
+    map: HashMap<K, V, S>,
+}
+impl<K, V> FrozenMap<K, V, RandomState> {
+    /// Create a new empty `FrozenMap` with the default hasher.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Un-freeze this `FrozenMap`, returning the underlying [`HashMap`].
+    #[must_use]
+    pub fn thaw(self) -> HashMap<K, V> {
+        self.map
+    }
+}
+impl<K, V, S> FrozenMap<K, V, S> {
+    /// Create a new `FrozenMap` from the entries of `iter` for which `keep`
+    /// returns `true`.
+    #[must_use]
+    pub fn from_iter_if(
+        iter: impl IntoIterator<Item = (K, V)>,
+        keep: impl Fn(&K, &V) -> bool,
+    ) -> Self
+    where
+        K: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        iter.into_iter().filter(|(k, v)| keep(k, v)).collect()
+    }
+
+    /// Create a new `FrozenMap` from a list of pairs, failing on the first
+    /// duplicate key.
+    ///
+    /// The error holds the index in `pairs` of the duplicate (that is, the
+    /// second occurrence of the key), along with the key itself.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let pairs = vec![("a", 1), ("b", 2), ("a", 3)];
+    /// assert_eq!(FrozenMap::<_, _>::from_pairs_checked(pairs), Err((2, "a")));
+    /// ```
+    pub fn from_pairs_checked(pairs: Vec<(K, V)>) -> Result<Self, (usize, K)>
+    where
+        K: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let mut map = HashMap::with_capacity_and_hasher(pairs.len(), S::default());
+        for (i, (k, v)) in pairs.into_iter().enumerate() {
+            if map.contains_key(&k) {
+                return Err((i, k));
+            }
+            map.insert(k, v);
+        }
+        Ok(map.freeze())
+    }
+
+    /// Create a new `FrozenMap` from the entries of `iter`, using the given
+    /// hasher.
+    ///
+    /// Unlike [`collect`](Iterator::collect), this does not need `S` to
+    /// implement [`Default`], so it can be used with seeded or keyed hashers.
+    ///
+    /// ```rust
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::{BuildHasher, Hasher};
+    ///
+    /// use frozenset::FrozenMap;
+    ///
+    /// /// A hasher seeded with a fixed value, which has no sensible default.
+    /// #[derive(Debug, Clone)]
+    /// struct Seeded(u64);
+    /// impl BuildHasher for Seeded {
+    ///     type Hasher = DefaultHasher;
+    ///
+    ///     fn build_hasher(&self) -> DefaultHasher {
+    ///         let mut hasher = DefaultHasher::new();
+    ///         hasher.write_u64(self.0);
+    ///         hasher
+    ///     }
+    /// }
+    ///
+    /// let map = FrozenMap::collect_with_hasher([("a", 1), ("b", 2)], Seeded(42));
+    /// assert_eq!(map.get("b"), Some(&2));
+    /// assert_eq!(map.hasher().0, 42);
+    /// let reversed = FrozenMap::collect_with_hasher([("b", 2), ("a", 1)], Seeded(7));
+    /// assert_eq!(map, reversed);
+    /// ```
+    #[must_use]
+    pub fn collect_with_hasher(
+        iter: impl IntoIterator<Item = (K, V)>,
+        hasher: S,
+    ) -> Self
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        let mut map = HashMap::with_hasher(hasher);
+        map.extend(iter);
+        map.freeze()
+    }
+
+    /// Decode a `FrozenMap` from the output of
+    /// [`to_canonical_bytes`](FrozenMap::to_canonical_bytes).
+    ///
+    /// Only canonical encodings are accepted, so this returns `None` if the
+    /// keys are not in strictly increasing order, or if there are any bytes
+    /// left over, as well as if any key or value fails to decode.
+    #[must_use]
+    pub fn from_canonical_bytes(mut bytes: &[u8]) -> Option<Self>
+    where
+        K: CanonicalBytes + Ord + Hash,
+        V: CanonicalBytes,
+        S: BuildHasher + Default,
+    {
+        let len = u64::read_canonical(bytes.get(..8)?)?;
+        bytes = &bytes[8..];
+        let mut entries: Vec<(K, V)> = Vec::new();
+        for _ in 0..len {
+            let k: K = read_length_prefixed(&mut bytes)?;
+            let v: V = read_length_prefixed(&mut bytes)?;
+            if entries.last().is_some_and(|(last, _)| last >= &k) {
+                return None;
+            }
+            entries.push((k, v));
+        }
+        bytes.is_empty().then(|| entries.into_iter().collect())
+    }
+
+    /// Re-key this map, computing each entry's new key from its old key and
+    /// value.
+    ///
+    /// If several entries produce the same new key, the last one visited wins;
+    /// as the iteration order of a map is unspecified, so is which entry that
+    /// is. Use [`try_rekey`](Self::try_rekey) to detect collisions instead.
+    #[must_use]
+    pub fn rekey<K2: Eq + Hash>(
+        self,
+        new_key: impl Fn(&K, &V) -> K2,
+    ) -> FrozenMap<K2, V, S>
+    where
+        S: BuildHasher + Default,
+    {
+        self.map
+            .into_iter()
+            .map(|(k, v)| (new_key(&k, &v), v))
+            .collect()
+    }
+
+    /// Re-key this map, computing each entry's new key from its old key and
+    /// value.
+    ///
+    /// Unlike [`rekey`](Self::rekey), this fails if two entries produce the
+    /// same new key, returning the new key computed for the second of them
+    /// (in iteration order).
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let map: FrozenMap<&str, i32> = [("a", 1), ("b", 2), ("c", 1)].into();
+    /// assert!(map.clone().try_rekey(|k, _| k.to_uppercase()).is_ok());
+    /// assert_eq!(map.try_rekey(|_, v| *v), Err(1));
+    /// ```
+    pub fn try_rekey<K2: Eq + Hash>(
+        self,
+        new_key: impl Fn(&K, &V) -> K2,
+    ) -> Result<FrozenMap<K2, V, S>, K2>
+    where
+        S: BuildHasher + Default,
+    {
+        let mut map =
+            HashMap::with_capacity_and_hasher(self.map.len(), S::default());
+        for (k, v) in self.map {
+            let k2 = new_key(&k, &v);
+            if map.contains_key(&k2) {
+                return Err(k2);
+            }
+            map.insert(k2, v);
+        }
+        Ok(map.freeze())
+    }
+
+    /// Get the smallest key in this map, if it is non-empty.
+    #[must_use]
+    pub fn min_key(&self) -> Option<&K>
+    where
+        K: Ord,
+    {
+        self.map.keys().min()
+    }
+
+    /// Get the largest key in this map, if it is non-empty.
+    #[must_use]
+    pub fn max_key(&self) -> Option<&K>
+    where
+        K: Ord,
+    {
+        self.map.keys().max()
+    }
+
+    /// Get the entry with the smallest value in this map, if it is non-empty.
+    ///
+    /// If several entries share the smallest value, which of them is returned
+    /// is unspecified.
+    #[must_use]
+    pub fn min_by_value(&self) -> Option<(&K, &V)>
+    where
+        V: Ord,
+    {
+        self.map.iter().min_by_key(|(_, v)| *v)
+    }
+
+    /// Get the entry with the largest value in this map, if it is non-empty.
+    ///
+    /// If several entries share the largest value, which of them is returned
+    /// is unspecified.
+    #[must_use]
+    pub fn max_by_value(&self) -> Option<(&K, &V)>
+    where
+        V: Ord,
+    {
+        self.map.iter().max_by_key(|(_, v)| *v)
+    }
+
+    /// Collect the distinct values of this map into a `FrozenSet`, without
+    /// cloning them.
+    ///
+    /// The returned set holds references into this map, so it cannot outlive
+    /// it; clone the values instead if the set needs to be kept independently.
+    #[must_use]
+    pub fn distinct_values_frozen(&self) -> FrozenSet<&V, S>
+    where
+        V: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        self.map.values().collect()
+    }
+
+    /// Check that every key of this map is in `allowed`.
+    ///
+    /// If any keys are not allowed, they are all returned as the error, which
+    /// is useful for reporting every unexpected key at once.
+    ///
+    /// ```rust
+    /// use frozenset::{FrozenMap, FrozenSet};
+    ///
+    /// let allowed: FrozenSet<&str> = ["host", "port"].into();
+    /// let config: FrozenMap<&str, i32> = [("port", 80), ("colour", 1)].into();
+    /// assert_eq!(config.keys_are_subset_of(&allowed), Err(["colour"].into()));
+    /// ```
+    pub fn keys_are_subset_of<S2: BuildHasher>(
+        &self,
+        allowed: &FrozenSet<K, S2>,
+    ) -> Result<(), FrozenSet<K, S>>
+    where
+        K: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let disallowed: FrozenSet<K, S> = self
+            .map
+            .keys()
+            .filter(|k| !allowed.contains(*k))
+            .cloned()
+            .collect();
+        if disallowed.is_empty() {
+            Ok(())
+        } else {
+            Err(disallowed)
+        }
+    }
+
+    /// Count how many keys map to each distinct value in this map.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let status: FrozenMap<i32, &str> = [(1, "ok"), (2, "failed"), (3, "ok")].into();
+    /// let counts = status.value_counts();
+    /// assert_eq!(counts, [("ok", 2), ("failed", 1)].into());
+    /// ```
+    #[must_use]
+    pub fn value_counts(&self) -> FrozenMap<V, usize, S>
+    where
+        V: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let mut counts = HashMap::<V, usize, S>::default();
+        for v in self.map.values() {
+            *counts.entry(v.clone()).or_insert(0) += 1;
+        }
+        counts.freeze()
+    }
+
+    /// Perform a full outer join of this map with `other`, pairing up the
+    /// values for every key in either map.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let left: FrozenMap<i32, char> = [(1, 'a'), (2, 'b')].into();
+    /// let right: FrozenMap<i32, bool> = [(2, true), (3, false)].into();
+    /// let joined = left.outer_join(&right);
+    /// assert_eq!(joined[&1], (Some('a'), None));
+    /// assert_eq!(joined[&2], (Some('b'), Some(true)));
+    /// assert_eq!(joined[&3], (None, Some(false)));
+    /// ```
+    #[must_use]
+    pub fn outer_join<V2: Clone, S2: BuildHasher>(
+        &self,
+        other: &FrozenMap<K, V2, S2>,
+    ) -> FrozenMap<K, (Option<V>, Option<V2>), S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone,
+        S: BuildHasher + Default,
+    {
+        let left = self
+            .map
+            .iter()
+            .map(|(k, v)| (k.clone(), (Some(v.clone()), other.map.get(k).cloned())));
+        let right = other
+            .map
+            .iter()
+            .filter(|(k, _)| !self.map.contains_key(*k))
+            .map(|(k, v2)| (k.clone(), (None, Some(v2.clone()))));
+        left.chain(right).collect()
+    }
+
+    /// Perform an inner join of this map with `other`, pairing up the values
+    /// for every key in both maps.
+    #[must_use]
+    pub fn inner_join<V2: Clone, S2: BuildHasher>(
+        &self,
+        other: &FrozenMap<K, V2, S2>,
+    ) -> FrozenMap<K, (V, V2), S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone,
+        S: BuildHasher + Default,
+    {
+        self.join(other, |v, v2| (v.clone(), v2.clone()))
+    }
+
+    /// Perform an inner join of this map with `other`, combining the values
+    /// for every key in both maps with `combine`.
+    ///
+    /// This iterates over the smaller of the two maps, looking each key up in
+    /// the larger.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let names: FrozenMap<u32, &str> = [(1, "ada"), (2, "bob"), (3, "cy")].into();
+    /// let ages: FrozenMap<u32, u8> = [(1, 36), (3, 41), (4, 29)].into();
+    /// let people = names.join(&ages, |name, age| format!("{name} ({age})"));
+    /// let expected: FrozenMap<u32, String> =
+    ///     [(1, "ada (36)".to_string()), (3, "cy (41)".to_string())].into();
+    /// assert_eq!(people, expected);
+    /// ```
+    #[must_use]
+    pub fn join<V2, W, S2: BuildHasher>(
+        &self,
+        other: &FrozenMap<K, V2, S2>,
+        combine: impl Fn(&V, &V2) -> W,
+    ) -> FrozenMap<K, W, S>
+    where
+        K: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        if self.map.len() <= other.map.len() {
+            self.map
+                .iter()
+                .filter_map(|(k, v)| {
+                    other.map.get(k).map(|v2| (k.clone(), combine(v, v2)))
+                })
+                .collect()
+        } else {
+            other
+                .map
+                .iter()
+                .filter_map(|(k, v2)| {
+                    self.map.get(k).map(|v| (k.clone(), combine(v, v2)))
+                })
+                .collect()
+        }
+    }
+
+    /// Partition the entries of this map into sub-maps, grouped by the key
+    /// computed by `group` for each entry.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let ages: FrozenMap<&str, u32> = [("ann", 7), ("bob", 42), ("cat", 70)].into();
+    /// let groups = ages.into_grouped_by(|_, age| *age >= 18);
+    /// assert_eq!(groups[&false], [("ann", 7)].into());
+    /// assert_eq!(groups[&true], [("bob", 42), ("cat", 70)].into());
+    /// ```
+    #[must_use]
+    pub fn into_grouped_by<G: Eq + Hash>(
+        self,
+        group: impl Fn(&K, &V) -> G,
+    ) -> FrozenMap<G, FrozenMap<K, V, S>, S>
+    where
+        K: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let mut groups = HashMap::<G, HashMap<K, V, S>, S>::default();
+        for (k, v) in self.map {
+            groups.entry(group(&k, &v)).or_default().insert(k, v);
+        }
+        groups
+            .into_iter()
+            .map(|(g, entries)| (g, entries.freeze()))
+            .collect()
+    }
+
+    /// Compute running aggregates over the entries of this map, in ascending
+    /// key order.
+    ///
+    /// Each key is paired with the result of applying `f` to the previous
+    /// aggregate (or `init`, for the first key) and that key's value. As a map
+    /// is unordered, this has to sort the entries first, which allocates.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let sales: FrozenMap<u32, u32> = [(3, 30), (1, 10), (2, 20)].into();
+    /// let totals = sales.cumulative(0, |total, v| total + v);
+    /// assert_eq!(totals, vec![(1, 10), (2, 30), (3, 60)]);
+    /// ```
+    #[must_use]
+    pub fn cumulative<A>(&self, init: A, f: impl Fn(&A, &V) -> A) -> Vec<(K, A)>
+    where
+        K: Ord + Clone,
+    {
+        let entries = self.sorted_refs();
+        let mut result: Vec<(K, A)> = Vec::with_capacity(entries.len());
+        for (k, v) in entries {
+            let acc = f(result.last().map_or(&init, |(_, acc)| acc), v);
+            result.push((k.clone(), acc));
+        }
+        result
+    }
+
+    /// Check whether this map has exactly the same keys as `other`, which may
+    /// have a different value type and hasher.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let ports: FrozenMap<&str, u16> = [("http", 80), ("https", 443)].into();
+    /// let names: FrozenMap<&str, String> =
+    ///     [("https", "secure".to_string()), ("http", "plain".to_string())].into();
+    /// let renamed: FrozenMap<&str, bool> = [("http", true), ("ftp", false)].into();
+    /// let fewer: FrozenMap<&str, bool> = [("http", true)].into();
+    /// assert!(ports.has_same_keys(&names));
+    /// assert!(!ports.has_same_keys(&renamed));
+    /// assert!(!ports.has_same_keys(&fewer));
+    /// ```
+    #[must_use]
+    pub fn has_same_keys<V2, S2: BuildHasher>(
+        &self,
+        other: &FrozenMap<K, V2, S2>,
+    ) -> bool
+    where
+        K: Eq + Hash,
+    {
+        self.map.len() == other.map.len()
+            && self.map.keys().all(|k| other.map.contains_key(k))
+    }
+
+    /// Get the value for `key`, panicking with `msg` if it is missing.
+    ///
+    /// This mirrors [`Option::expect`], giving a more helpful message than
+    /// [`Index`] does when a required key is missing.
+    ///
+    /// # Panics
+    ///
+    /// Panics with `msg` if `key` is not in the map.
+    ///
+    /// ```rust
+    /// use std::panic;
+    ///
+    /// use frozenset::FrozenMap;
+    ///
+    /// let config: FrozenMap<&str, u16> = [("port", 80)].into();
+    /// assert_eq!(*config.expect("port", "port must be configured"), 80);
+    ///
+    /// let payload = panic::catch_unwind(|| *config.expect("host", "host must be configured"))
+    ///     .unwrap_err();
+    /// let message = payload
+    ///     .downcast_ref::<String>()
+    ///     .map(String::as_str)
+    ///     .or_else(|| payload.downcast_ref::<&str>().copied())
+    ///     .unwrap();
+    /// assert!(message.contains("host must be configured"));
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn expect<Q: Eq + Hash + ?Sized>(&self, key: &Q, msg: &str) -> &V
+    where
+        K: Eq + Hash + Borrow<Q>,
+        S: BuildHasher,
+    {
+        self.map.get(key).expect(msg)
+    }
+
+    /// Count how many keys of this map fall into each bucket, as computed by
+    /// `bucket`.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let map: FrozenMap<&str, ()> = [("apple", ()), ("avocado", ()), ("banana", ())].into();
+    /// let counts = map.count_keys_by(|k| k.chars().next());
+    /// assert_eq!(counts, [(Some('a'), 2), (Some('b'), 1)].into());
+    /// ```
+    #[must_use]
+    pub fn count_keys_by<B: Eq + Hash>(
+        &self,
+        bucket: impl Fn(&K) -> B,
+    ) -> FrozenMap<B, usize, S>
+    where
+        S: BuildHasher + Default,
+    {
+        let mut counts = HashMap::<B, usize, S>::default();
+        for k in self.map.keys() {
+            *counts.entry(bucket(k)).or_insert(0) += 1;
+        }
+        counts.freeze()
+    }
+
+    /// Replace the values of keys which are also in `updates` with the
+    /// updated values.
+    ///
+    /// Unlike a merge, keys which are only in `updates` are ignored, so the
+    /// result always has the same keys as this map.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let config: FrozenMap<&str, i32> = [("port", 80), ("workers", 4)].into();
+    /// let updates: FrozenMap<&str, i32> = [("port", 8080), ("colour", 1)].into();
+    /// let patched = config.override_values(&updates);
+    /// assert_eq!(patched, [("port", 8080), ("workers", 4)].into());
+    /// ```
+    #[must_use]
+    pub fn override_values<S2: BuildHasher>(
+        mut self,
+        updates: &FrozenMap<K, V, S2>,
+    ) -> Self
+    where
+        K: Eq + Hash,
+        V: Clone,
+        S: BuildHasher,
+    {
+        for (k, v) in &mut self.map {
+            if let Some(update) = updates.map.get(k) {
+                v.clone_from(update);
+            }
+        }
+        self
+    }
+
+    /// Get the entries of this map sorted by value, in ascending order.
+    ///
+    /// This allocates a new `Vec` of references. Entries with equal values are
+    /// ordered arbitrarily.
+    #[must_use]
+    pub fn sorted_by_value(&self) -> Vec<(&K, &V)>
+    where
+        V: Ord,
+    {
+        let mut entries: Vec<_> = self.map.iter().collect();
+        entries.sort_unstable_by_key(|(_, v)| *v);
+        entries
+    }
+
+    /// Get the entries of this map sorted by value, in descending order.
+    ///
+    /// This allocates a new `Vec` of references. Entries with equal values are
+    /// ordered arbitrarily.
+    #[must_use]
+    pub fn sorted_by_value_desc(&self) -> Vec<(&K, &V)>
+    where
+        V: Ord,
+    {
+        let mut entries: Vec<_> = self.map.iter().collect();
+        entries.sort_unstable_by_key(|(_, v)| Reverse(*v));
+        entries
+    }
+
+    /// Get the value for each of `keys`, in order, substituting `default` for
+    /// any key which is missing.
+    ///
+    /// The returned references borrow from either this map or `default`, so
+    /// the result cannot outlive either of them.
+    #[must_use]
+    pub fn get_many_or<'a, Q: Eq + Hash + ?Sized>(
+        &'a self,
+        keys: &[&Q],
+        default: &'a V,
+    ) -> Vec<&'a V>
+    where
+        K: Eq + Hash + Borrow<Q>,
+        S: BuildHasher,
+    {
+        keys.iter()
+            .map(|k| self.map.get(*k).unwrap_or(default))
+            .collect()
+    }
+
+    /// Get a copy of this map without `key`, along with the value which was
+    /// removed, if there was one.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let map: FrozenMap<&str, u32> = [("a", 1), ("b", 2)].into();
+    /// let (rest, removed) = map.without_entry("a");
+    /// assert_eq!(removed, Some(1));
+    /// assert_eq!(rest, [("b", 2)].into());
+    ///
+    /// let (rest, removed) = map.without_entry("z");
+    /// assert_eq!(removed, None);
+    /// assert_eq!(rest, map);
+    /// ```
+    #[must_use]
+    pub fn without_entry<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> (Self, Option<V>)
+    where
+        K: Clone + Eq + Hash + Borrow<Q>,
+        V: Clone,
+        S: BuildHasher + Default,
+    {
+        let rest = self
+            .map
+            .iter()
+            .filter(|(k, _)| (*k).borrow() != key)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        (rest, self.map.get(key).cloned())
+    }
+
+    /// Assign each key of this map a distinct ID in `0..len`, in ascending key
+    /// order.
+    ///
+    /// Basing the IDs on the sort order of the keys, rather than the map's
+    /// unspecified iteration order, means that maps with the same keys always
+    /// get the same IDs.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let map: FrozenMap<&str, f64> = [("b", 0.5), ("c", 1.5), ("a", 2.5)].into();
+    /// assert_eq!(map.assign_ids(), [("a", 0), ("b", 1), ("c", 2)].into());
+    /// ```
+    #[must_use]
+    pub fn assign_ids(&self) -> FrozenMap<K, usize, S>
+    where
+        K: Ord + Clone + Hash,
+        S: BuildHasher + Default,
+    {
+        let mut keys: Vec<_> = self.map.keys().collect();
+        keys.sort_unstable();
+        keys.into_iter()
+            .enumerate()
+            .map(|(id, k)| (k.clone(), id))
+            .collect()
+    }
+
+    /// Split this map into the entries which satisfy `pred` and those which do
+    /// not, in a single pass and without cloning.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let map: FrozenMap<&str, u32> = [("a", 1), ("b", 2), ("c", 3)].into();
+    /// let (odd, even) = map.clone().split(|_, v| v % 2 == 1);
+    /// assert_eq!(odd, [("a", 1), ("c", 3)].into());
+    /// assert_eq!(even, [("b", 2)].into());
+    /// let reunited: FrozenMap<&str, u32> = odd.into_iter().chain(even).collect();
+    /// assert_eq!(reunited, map);
+    /// ```
+    #[must_use]
+    pub fn split(self, pred: impl Fn(&K, &V) -> bool) -> (Self, Self)
+    where
+        K: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let (matching, rest): (HashMap<K, V, S>, _) =
+            self.map.into_iter().partition(|(k, v)| pred(k, v));
+        (matching.freeze(), rest.freeze())
+    }
+
+    /// Iterate over the entries of this map in ascending key order, in pages
+    /// of up to `page_size` entries.
+    ///
+    /// All of the entries are sorted once, up front, when this is called.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let map: FrozenMap<u32, char> = [(3, 'c'), (1, 'a'), (5, 'e'), (2, 'b'), (4, 'd')].into();
+    /// let pages: Vec<_> = map.sorted_pages(2).collect();
+    /// assert_eq!(pages, [
+    ///     vec![(&1, &'a'), (&2, &'b')],
+    ///     vec![(&3, &'c'), (&4, &'d')],
+    ///     vec![(&5, &'e')],
+    /// ]);
+    /// assert_eq!(FrozenMap::<u32, char>::new().sorted_pages(2).count(), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page_size` is zero.
+    ///
+    /// ```rust,should_panic
+    /// use frozenset::FrozenMap;
+    ///
+    /// let map: FrozenMap<u32, char> = [(1, 'a')].into();
+    /// let _ = map.sorted_pages(0);
+    /// ```
+    pub fn sorted_pages(
+        &self,
+        page_size: usize,
+    ) -> impl Iterator<Item = Vec<(&K, &V)>>
+    where
+        K: Ord,
+    {
+        assert!(page_size > 0, "page size must be non-zero");
+        let entries = self.sorted_refs();
+        let mut entries = entries.into_iter();
+        std::iter::from_fn(move || {
+            let page: Vec<_> = entries.by_ref().take(page_size).collect();
+            (!page.is_empty()).then_some(page)
+        })
+    }
+
+    /// Re-index this map to have exactly the keys in `keys`.
+    ///
+    /// Values for keys in both are kept, keys only in `keys` get a clone of
+    /// `default`, and keys only in this map are dropped.
+    ///
+    /// ```rust
+    /// use frozenset::{FrozenMap, FrozenSet};
+    ///
+    /// let row: FrozenMap<&str, i32> = [("a", 1), ("z", 26)].into();
+    /// let columns: FrozenSet<&str> = ["a", "b"].into();
+    /// assert_eq!(row.reindex_to(&columns, &0), [("a", 1), ("b", 0)].into());
+    /// ```
+    #[must_use]
+    pub fn reindex_to<S2>(&self, keys: &FrozenSet<K, S2>, default: &V) -> Self
+    where
+        K: Clone + Eq + Hash,
+        V: Clone,
+        S: BuildHasher + Default,
+    {
+        keys.set
+            .iter()
+            .map(|k| (k.clone(), self.map.get(k).unwrap_or(default).clone()))
+            .collect()
+    }
+
+    /// Combine this map with `other` over the union of their keys, using
+    /// `self_default` or `other_default` in place of a missing value.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let a: FrozenMap<&str, i32> = [("x", 1), ("y", 2)].into();
+    /// let b: FrozenMap<&str, i32> = [("y", 10), ("z", 20)].into();
+    /// let sum = a.combine(&b, &0, &0, |a, b| a + b);
+    /// assert_eq!(sum, [("x", 1), ("y", 12), ("z", 20)].into());
+    /// ```
+    #[must_use]
+    pub fn combine<V2, W, S2: BuildHasher>(
+        &self,
+        other: &FrozenMap<K, V2, S2>,
+        self_default: &V,
+        other_default: &V2,
+        f: impl Fn(&V, &V2) -> W,
+    ) -> FrozenMap<K, W, S>
+    where
+        K: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let left = self.map.iter().map(|(k, v)| {
+            (k.clone(), f(v, other.map.get(k).unwrap_or(other_default)))
+        });
+        let right = other
+            .map
+            .iter()
+            .filter(|(k, _)| !self.map.contains_key(*k))
+            .map(|(k, v2)| (k.clone(), f(self_default, v2)));
+        left.chain(right).collect()
+    }
+
+    /// Get a copy of this map with the key `from` renamed to `to`.
+    ///
+    /// If `from` is not in the map, the copy is unchanged. If `to` is already
+    /// in the map, its value is overwritten by the value of `from`.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let map: FrozenMap<&str, i32> = [("colour", 1), ("size", 2)].into();
+    /// let renamed = map.rename_key("colour", "color");
+    /// assert_eq!(renamed, [("color", 1), ("size", 2)].into());
+    /// assert_eq!(map.rename_key("shape", "form"), map);
+    /// assert_eq!(map.rename_key("colour", "size"), [("size", 1)].into());
+    /// ```
+    #[must_use]
+    pub fn rename_key<Q: Eq + Hash + ?Sized>(&self, from: &Q, to: K) -> Self
+    where
+        K: Clone + Eq + Hash + Borrow<Q>,
+        V: Clone,
+        S: BuildHasher + Default,
+    {
+        let Some(value) = self.map.get(from) else {
+            return self
+                .map
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+        };
+        let mut map: HashMap<K, V, S> = self
+            .map
+            .iter()
+            .filter(|(k, _)| (*k).borrow() != from)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        map.insert(to, value.clone());
+        map.freeze()
+    }
+
+    /// Merge the values of runs of keys in the same group, as decided by
+    /// `same_group`, visiting the keys in ascending order.
+    ///
+    /// Each run is keyed by its first (smallest) key, and every later key is
+    /// compared against that first key with `same_group`. Values within a run
+    /// are folded together in ascending key order, as `combine(acc, next)`. As
+    /// a map is unordered, this has to sort the entries first, which
+    /// allocates.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let per_minute: FrozenMap<u32, u32> =
+    ///     [(0, 1), (30, 2), (60, 3), (90, 4)].into();
+    /// let per_hour = per_minute.coalesce(|start, k| k - start < 60, |a, b| a + b);
+    /// assert_eq!(per_hour, [(0, 3), (60, 7)].into());
+    /// ```
+    #[must_use]
+    pub fn coalesce(
+        &self,
+        same_group: impl Fn(&K, &K) -> bool,
+        combine: impl Fn(V, V) -> V,
+    ) -> Self
+    where
+        K: Ord + Clone + Hash,
+        V: Clone,
+        S: BuildHasher + Default,
+    {
+        let entries = self.sorted_refs();
+        let mut runs: Vec<(&K, V)> = Vec::new();
+        for (k, v) in entries {
+            match runs.pop() {
+                Some((start, acc)) if same_group(start, k) => {
+                    runs.push((start, combine(acc, v.clone())));
+                }
+                run => {
+                    runs.extend(run);
+                    runs.push((k, v.clone()));
+                }
+            }
+        }
+        runs.into_iter().map(|(k, v)| (k.clone(), v)).collect()
+    }
+
+    /// Transform the values of this map with `f`, which can carry state from
+    /// one entry to the next, visiting the entries in ascending key order.
+    ///
+    /// Visiting the entries in a fixed order makes the result deterministic,
+    /// but as a map is unordered, this has to sort the entries first, which
+    /// allocates.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let readings: FrozenMap<u32, i32> = [(1, 10), (2, 15), (3, 12)].into();
+    /// let deltas = readings.scan_values(None, |prev, _, &v| {
+    ///     let delta = prev.map_or(0, |p| v - p);
+    ///     *prev = Some(v);
+    ///     delta
+    /// });
+    /// assert_eq!(deltas, [(1, 0), (2, 5), (3, -3)].into());
+    /// ```
+    #[must_use]
+    pub fn scan_values<St, W>(
+        &self,
+        init: St,
+        mut f: impl FnMut(&mut St, &K, &V) -> W,
+    ) -> FrozenMap<K, W, S>
+    where
+        K: Ord + Clone + Hash,
+        S: BuildHasher + Default,
+    {
+        let entries = self.sorted_refs();
+        let mut state = init;
+        entries
+            .into_iter()
+            .map(|(k, v)| (k.clone(), f(&mut state, k, v)))
+            .collect()
+    }
+
+    /// Get the entries of this map in ascending key order, without cloning
+    /// them.
+    ///
+    /// The returned references borrow from this map. Sorting them takes
+    /// O(n log n) time, and allocates a new `Vec`.
+    #[must_use]
+    pub fn sorted_refs(&self) -> Vec<(&K, &V)>
+    where
+        K: Ord,
+    {
+        let mut entries: Vec<_> = self.map.iter().collect();
+        entries.sort_unstable_by_key(|(k, _)| *k);
+        entries
+    }
+
+    /// Wrap this map with a default value, which indexing returns for missing
+    /// keys instead of panicking.
+    #[must_use]
+    pub fn with_default(self, default: V) -> DefaultingFrozenMap<K, V, S> {
+        DefaultingFrozenMap {
+            map: self,
+            default,
+        }
+    }
+
+    /// Check this map against a simple schema, collecting every violation
+    /// rather than stopping at the first.
+    ///
+    /// Every key in `required` must be present, and every entry must satisfy
+    /// `value_ok`. Missing keys are reported before invalid values, but the
+    /// order within each kind of violation is unspecified.
+    ///
+    /// ```rust
+    /// use frozenset::{FrozenMap, FrozenSet, SchemaViolation};
+    ///
+    /// let required: FrozenSet<&str> = ["host", "port"].into();
+    /// let config: FrozenMap<&str, i32> = [("port", -1)].into();
+    /// let violations = config.validate(&required, |_, v| *v >= 0);
+    /// assert_eq!(
+    ///     violations,
+    ///     vec![SchemaViolation::MissingKey("host"), SchemaViolation::InvalidValue("port")]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn validate<S2>(
+        &self,
+        required: &FrozenSet<K, S2>,
+        value_ok: impl Fn(&K, &V) -> bool,
+    ) -> Vec<SchemaViolation<K>>
+    where
+        K: Clone + Eq + Hash,
+        S: BuildHasher,
+    {
+        let missing = required
+            .set
+            .iter()
+            .filter(|k| !self.map.contains_key(*k))
+            .map(|k| SchemaViolation::MissingKey(k.clone()));
+        let invalid = self
+            .map
+            .iter()
+            .filter(|(k, v)| !value_ok(k, v))
+            .map(|(k, _)| SchemaViolation::InvalidValue(k.clone()));
+        missing.chain(invalid).collect()
+    }
+
+    /// Get the entry of this map with the largest `score`, if it is non-empty.
+    ///
+    /// If several entries share the largest score, which of them is returned
+    /// is unspecified.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let stock: FrozenMap<&str, (u32, u32)> = [("nut", (10, 2)), ("bolt", (3, 9))].into();
+    /// let most_valuable = stock.max_entry_by(|_, (count, price)| count * price);
+    /// assert_eq!(most_valuable, Some((&"bolt", &(3, 9))));
+    /// ```
+    #[must_use]
+    pub fn max_entry_by<B: Ord>(
+        &self,
+        score: impl Fn(&K, &V) -> B,
+    ) -> Option<(&K, &V)> {
+        self.map.iter().max_by_key(|(k, v)| score(k, v))
+    }
+
+    /// Get the entry of this map with the smallest `score`, if it is
+    /// non-empty.
+    ///
+    /// If several entries share the smallest score, which of them is returned
+    /// is unspecified.
+    #[must_use]
+    pub fn min_entry_by<B: Ord>(
+        &self,
+        score: impl Fn(&K, &V) -> B,
+    ) -> Option<(&K, &V)> {
+        self.map.iter().min_by_key(|(k, v)| score(k, v))
+    }
+
+    /// Get the sub-map of the `k` entries of this map with the largest values.
+    ///
+    /// This keeps a bounded heap of the best candidates seen so far, so takes
+    /// O(m log k) time for a map of `m` entries. If several entries share the
+    /// value at the boundary, which of them are kept is unspecified.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let scores: FrozenMap<&str, u32> = [("a", 5), ("b", 9), ("c", 7), ("d", 1)].into();
+    /// let podium: FrozenMap<&str, u32> = [("b", 9), ("c", 7)].into();
+    /// assert_eq!(scores.top_k_by_value(2), podium);
+    ///
+    /// let tied: FrozenMap<&str, u32> = [("a", 3), ("b", 3), ("c", 1)].into();
+    /// let top = tied.top_k_by_value(1);
+    /// assert_eq!(top.len(), 1);
+    /// assert_eq!(top.values().next(), Some(&3));
+    ///
+    /// assert_eq!(scores.top_k_by_value(10), scores);
+    /// assert_eq!(scores.top_k_by_value(usize::MAX), scores);
+    /// assert!(scores.top_k_by_value(0).is_empty());
+    /// ```
+    #[must_use]
+    pub fn top_k_by_value(&self, k: usize) -> FrozenMap<K, V, S>
+    where
+        K: Clone + Eq + Hash,
+        V: Ord + Clone,
+        S: BuildHasher + Default,
+    {
+        let mut heap = BinaryHeap::with_capacity(k.min(self.map.len()) + 1);
+        for (i, v) in self.map.values().enumerate() {
+            heap.push(Reverse((v, i)));
+            if heap.len() > k {
+                heap.pop();
+            }
+        }
+        let mut kept: Vec<usize> =
+            heap.into_iter().map(|Reverse((_, i))| i).collect();
+        kept.sort_unstable();
+        self.map
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| kept.binary_search(i).is_ok())
+            .map(|(_, (k, v))| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    /// Expand each value of this map into a set of derived values, computed
+    /// by `f`.
+    ///
+    /// ```rust
+    /// use frozenset::{FrozenMap, FrozenSet};
+    ///
+    /// let titles: FrozenMap<u32, &str> = [(1, "the cat sat"), (2, "")].into();
+    /// let words = titles.explode_values(|title| title.split_whitespace());
+    /// assert_eq!(words[&1], FrozenSet::from(["cat", "sat", "the"]));
+    /// assert!(words[&2].is_empty());
+    /// ```
+    #[must_use]
+    pub fn explode_values<W, I>(
+        self,
+        f: impl Fn(&V) -> I,
+    ) -> FrozenMap<K, FrozenSet<W, S>, S>
+    where
+        K: Clone + Eq + Hash,
+        W: Eq + Hash,
+        I: IntoIterator<Item = W>,
+        S: BuildHasher + Default,
+    {
+        self.map
+            .into_iter()
+            .map(|(k, v)| (k, f(&v).into_iter().collect()))
+            .collect()
+    }
+
+    /// Encode this map as bytes which depend only on its entries, and not on
+    /// its hasher or the order in which they were inserted.
+    ///
+    /// The encoding is the number of entries, then each key and value in
+    /// increasing key order, each preceded by the length of its encoding (all
+    /// lengths being big-endian `u64`s). This makes it suitable for content
+    /// addressing; it can be decoded with
+    /// [`from_canonical_bytes`](FrozenMap::from_canonical_bytes).
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let mut entries: Vec<(String, u32)> =
+    ///     ["a", "bb", "ccc", "dd", "e"].map(|k| (k.into(), k.len() as u32)).into();
+    /// let map: FrozenMap<String, u32> = entries.iter().cloned().collect();
+    /// let bytes = map.to_canonical_bytes();
+    /// for _ in 0..entries.len() {
+    ///     entries.rotate_left(1);
+    ///     let rotated: FrozenMap<String, u32> = entries.iter().cloned().collect();
+    ///     assert_eq!(rotated.to_canonical_bytes(), bytes);
+    /// }
+    /// assert_eq!(FrozenMap::from_canonical_bytes(&bytes), Some(map));
+    /// assert_eq!(FrozenMap::<String, u32>::from_canonical_bytes(&bytes[1..]), None);
+    /// ```
+    #[must_use]
+    pub fn to_canonical_bytes(&self) -> Vec<u8>
+    where
+        K: CanonicalBytes + Ord,
+        V: CanonicalBytes,
+    {
+        let mut out = Vec::new();
+        (self.map.len() as u64).write_canonical(&mut out);
+        for (k, v) in self.sorted_refs() {
+            write_length_prefixed(k, &mut out);
+            write_length_prefixed(v, &mut out);
+        }
+        out
+    }
+
+    /// Get the entries of this map whose key satisfies `key_pred` and whose
+    /// value satisfies `value_pred`.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let limits: FrozenMap<&str, u32> =
+    ///     [("net.rx", 10), ("net.tx", 900), ("disk.rx", 50), ("net.mtu", 1500)].into();
+    /// let busy = limits.select(|k| k.starts_with("net."), |v| (5..=1000).contains(v));
+    /// assert_eq!(busy, [("net.rx", 10), ("net.tx", 900)].into());
+    /// assert_eq!(limits.into_selected(|k| k.starts_with("disk."), |_| true).len(), 1);
+    /// ```
+    #[must_use]
+    pub fn select(
+        &self,
+        key_pred: impl Fn(&K) -> bool,
+        value_pred: impl Fn(&V) -> bool,
+    ) -> FrozenMap<K, V, S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone,
+        S: BuildHasher + Default,
+    {
+        self.map
+            .iter()
+            .filter(|(k, v)| key_pred(k) && value_pred(v))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    /// Get the entries of this map whose key satisfies `key_pred` and whose
+    /// value satisfies `value_pred`, consuming this map.
+    ///
+    /// This is the counterpart to [`select`](Self::select), without the need
+    /// to clone the entries.
+    #[must_use]
+    pub fn into_selected(
+        self,
+        key_pred: impl Fn(&K) -> bool,
+        value_pred: impl Fn(&V) -> bool,
+    ) -> FrozenMap<K, V, S>
+    where
+        K: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        self.map
+            .into_iter()
+            .filter(|(k, v)| key_pred(k) && value_pred(v))
+            .collect()
+    }
+}
+impl<K, K2, V, S> FrozenMap<K, FrozenMap<K2, V, S>, S> {
+    /// Flatten this map of maps into a single map, keyed by `(outer, inner)`
+    /// pairs of keys.
+    ///
+    /// Outer keys whose inner map is empty have no entries to contribute, so
+    /// they do not appear in the result.
+    #[must_use]
+    pub fn flatten(self) -> FrozenMap<(K, K2), V, S>
+    where
+        K: Clone + Eq + Hash,
+        K2: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        self.map
+            .into_iter()
+            .flat_map(|(outer, inner)| {
+                inner
+                    .map
+                    .into_iter()
+                    .map(move |(k, v)| ((outer.clone(), k), v))
+            })
+            .collect()
+    }
+}
+impl<K, K2, V, S> FrozenMap<(K, K2), V, S> {
+    /// Un-flatten this map keyed by `(outer, inner)` pairs into a map of maps,
+    /// reversing [`flatten`](FrozenMap::flatten).
+    #[must_use]
+    pub fn unflatten(self) -> FrozenMap<K, FrozenMap<K2, V, S>, S>
+    where
+        K: Eq + Hash,
+        K2: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let mut outer = HashMap::<K, HashMap<K2, V, S>, S>::default();
+        for ((k, k2), v) in self.map {
+            outer.entry(k).or_default().insert(k2, v);
+        }
+        outer
+            .into_iter()
+            .map(|(k, inner)| (k, inner.freeze()))
+            .collect()
+    }
+}
+impl<S> FrozenMap<String, String, S> {
+    /// Create a new `FrozenMap` from the variables of `vars` whose names start
+    /// with `prefix`, with the prefix stripped from their names.
+    ///
+    /// `vars` will typically be [`std::env::vars`]. If several variables have
+    /// the same name once stripped, the last one wins.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    ///
+    /// let vars = [("APP_PORT", "80"), ("HOME", "/root"), ("APP_PORT", "8080")];
+    /// let vars = vars.map(|(k, v)| (k.to_string(), v.to_string()));
+    /// let config: FrozenMap<String, String> = FrozenMap::from_env_vars(vars, "APP_");
+    /// assert_eq!(config, [("PORT".to_string(), "8080".to_string())].into());
+    /// ```
+    #[must_use]
+    pub fn from_env_vars(
+        vars: impl IntoIterator<Item = (String, String)>,
+        prefix: &str,
+    ) -> Self
+    where
+        S: BuildHasher + Default,
+    {
+        vars.into_iter()
+            .filter_map(|(k, v)| Some((k.strip_prefix(prefix)?.to_string(), v)))
+            .collect()
+    }
+}
+impl<K, V, S> Deref for FrozenMap<K, V, S> {
+    type Target = HashMap<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+impl<K, V, S: BuildHasher + Default> Default for FrozenMap<K, V, S> {
+    fn default() -> Self {
+        Self {
+            map: HashMap::default(),
+        }
+    }
+}
+impl<T, K, V, S> From<T> for FrozenMap<K, V, S>
+where
+    HashMap<K, V, S>: From<T>,
+{
+    fn from(map: T) -> Self {
+        Self {
+            map: map.into(),
+        }
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)>
+    for FrozenMap<K, V, S>
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        Self {
+            map: iter.into_iter().collect(),
+        }
+    }
+}
+impl<K: Eq + Hash + Borrow<Q>, Q: Eq + Hash + ?Sized, V, S: BuildHasher> Index<&Q>
+    for FrozenMap<K, V, S>
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &Self::Output {
+        &self.map[key]
+    }
+}
+impl<K, V, S> IntoIterator for FrozenMap<K, V, S> {
+    type IntoIter = hash_map::IntoIter<K, V>;
+    type Item = (K, V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter()
+    }
+}
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for FrozenMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map.eq(&other.map)
+    }
+}
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for FrozenMap<K, V, S> {
+}
+impl<K: UnwindSafe, V: UnwindSafe, S: UnwindSafe> UnwindSafe for FrozenMap<K, V, S> {
+}
+impl<K: Hash, V: Hash, S> Hash for FrozenMap<K, V, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // A fairly simple hash algorithm. Probably not *great* from a
+        // collision-avoidance perspective, but it's fast, simple, and
+        // consistent.
+
+        // The overall hash is the XOR of the hashes of all the key-value pairs,
+        // which will be consistent no matter the iteration order.
+        let mut overall_hash = 0;
+        for (k, v) in &self.map {
+            let mut hasher = DefaultHasher::new();
+            k.hash(&mut hasher);
+            v.hash(&mut hasher);
+            overall_hash ^= hasher.finish();
+        }
+        overall_hash.hash(state);
+    }
+}
+
+/// A `DefaultingFrozenMap` is a [`FrozenMap`] paired with a default value,
+/// created by [`FrozenMap::with_default`].
+///
+/// Indexing a `DefaultingFrozenMap` never panics: missing keys give a reference
+/// to the default value. It will [`Deref`] to the underlying [`FrozenMap`] for
+/// everything else.
+///
+/// ```rust
+/// use frozenset::FrozenMap;
+///
+/// let map: FrozenMap<&str, i32> = [("a", 1)].into();
+/// let map = map.with_default(0);
+/// assert_eq!(map["a"], 1);
+/// assert_eq!(map["b"], 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DefaultingFrozenMap<K, V, S = RandomState> {
+    map: FrozenMap<K, V, S>,
+    default: V,
+}
+impl<K, V, S> DefaultingFrozenMap<K, V, S> {
+    /// Get the default value, returned for missing keys.
+    #[must_use]
+    pub fn default_value(&self) -> &V {
+        &self.default
+    }
+
+    /// Unwrap this `DefaultingFrozenMap`, returning the underlying
+    /// [`FrozenMap`] and the default value.
+    #[must_use]
+    pub fn into_parts(self) -> (FrozenMap<K, V, S>, V) {
+        (self.map, self.default)
+    }
+}
+impl<K, V, S> Deref for DefaultingFrozenMap<K, V, S> {
+    type Target = FrozenMap<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+impl<K: Eq + Hash + Borrow<Q>, Q: Eq + Hash + ?Sized, V, S: BuildHasher> Index<&Q>
+    for DefaultingFrozenMap<K, V, S>
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.map.map.get(key).unwrap_or(&self.default)
+    }
+}
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq
+    for DefaultingFrozenMap<K, V, S>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.map.eq(&other.map) && self.default.eq(&other.default)
+    }
+}
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for DefaultingFrozenMap<K, V, S> {
+}
+impl<K: Hash, V: Hash, S> Hash for DefaultingFrozenMap<K, V, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.map.hash(state);
+        self.default.hash(state);
+    }
+}
+
+/// A `SchemaViolation` is a problem found by [`FrozenMap::validate`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SchemaViolation<K> {
+    /// A required key was missing from the map.
+    MissingKey(K),
+    /// The value for this key was rejected.
+    InvalidValue(K),
+}
+
+/// A `FrozenSet` is a wrapper around a [`HashSet`] that implements [`Hash`].
+///
+/// It is a logic error to mutate any element of the set (via internal
+/// mutability) after it has been frozen.
+///
+/// For convenience, `FrozenSet` implements all of [`HashSet`]'s traits, and
+/// will [`Deref`] to [`HashSet`], so you can use it as a drop-in replacement
+/// for an `&HashSet`.
+#[derive(Debug, Clone)]
+pub struct FrozenSet<T, S = RandomState> {
+    set: HashSet<T, S>,
+}
+impl<T> FrozenSet<T> {
+    /// Create a new empty `FrozenSet` with the default hasher.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            set: HashSet::new(),
+        }
+    }
+
+    /// Un-freeze this `FrozenSet`, returning the underlying [`HashSet`].
+    #[must_use]
+    pub fn thaw(self) -> HashSet<T> {
+        self.set
+    }
+}
+impl<T, S> FrozenSet<T, S> {
+    /// Create a new `FrozenSet` from the elements of `iter` for which `keep`
+    /// returns `true`.
+    #[must_use]
+    pub fn from_iter_if(
+        iter: impl IntoIterator<Item = T>,
+        keep: impl Fn(&T) -> bool,
+    ) -> Self
+    where
+        T: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        iter.into_iter().filter(|v| keep(v)).collect()
+    }
+
+    /// Create a new `FrozenSet` from the elements of `iter`, also returning
+    /// the elements which were dropped as duplicates.
+    ///
+    /// The first occurrence of each element is kept in the set, and every
+    /// later occurrence is returned, in the order they were encountered.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let (set, duplicates) = FrozenSet::<_>::from_iter_with_duplicates([1, 2, 1, 3, 1]);
+    /// assert_eq!(set, [1, 2, 3].into());
+    /// assert_eq!(duplicates, vec![1, 1]);
+    /// ```
+    #[must_use]
+    pub fn from_iter_with_duplicates(
+        iter: impl IntoIterator<Item = T>,
+    ) -> (Self, Vec<T>)
+    where
+        T: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let mut set = HashSet::<T, S>::default();
+        let mut duplicates = Vec::new();
+        for v in iter {
+            if set.contains(&v) {
+                duplicates.push(v);
+            } else {
+                set.insert(v);
+            }
+        }
+        (set.freeze(), duplicates)
+    }
+
+    /// Create a new `FrozenSet` from the elements of `iter`, using the given
+    /// hasher.
+    ///
+    /// Unlike [`collect`](Iterator::collect), this does not need `S` to
+    /// implement [`Default`], so it can be used with seeded or keyed hashers.
+    ///
+    /// ```rust
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// use frozenset::FrozenSet;
+    ///
+    /// let hasher = RandomState::new();
+    /// let set = FrozenSet::collect_with_hasher([1, 2, 3], hasher.clone());
+    /// assert!(set.contains(&2));
+    /// assert_eq!(set, FrozenSet::collect_with_hasher([3, 2, 1], hasher));
+    /// ```
+    #[must_use]
+    pub fn collect_with_hasher(iter: impl IntoIterator<Item = T>, hasher: S) -> Self
+    where
+        T: Eq + Hash,
+        S: BuildHasher,
+    {
+        let mut set = HashSet::with_hasher(hasher);
+        set.extend(iter);
+        set.freeze()
+    }
+
+    /// Remove the elements of each set of `subtract` from `base`, in order.
+    ///
+    /// This stops early once no elements are left, without consuming the rest
+    /// of `subtract`.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let everyone: FrozenSet<u32> = (1..=10).collect();
+    /// let exclusions: [FrozenSet<u32>; 3] = [[1, 2].into(), [2, 3, 4].into(), [10].into()];
+    /// let kept = FrozenSet::difference_fold(everyone, exclusions);
+    /// assert_eq!(kept, (5..=9).collect());
+    /// ```
+    #[must_use]
+    pub fn difference_fold(
+        base: FrozenSet<T, S>,
+        subtract: impl IntoIterator<Item = FrozenSet<T, S>>,
+    ) -> FrozenSet<T, S>
+    where
+        T: Eq + Hash,
+        S: BuildHasher,
+    {
+        let mut set = base.set;
+        for other in subtract {
+            if set.is_empty() {
+                break;
+            }
+            set.retain(|v| !other.set.contains(v));
+        }
+        set.freeze()
+    }
+
+    /// Create a new `FrozenSet` from the elements of `iter`, keeping only the
+    /// first element with each distinct `fingerprint`.
+    ///
+    /// The fingerprint can be a hash of a normalised form of each element, so
+    /// that near-duplicates are dropped; note that distinct elements which
+    /// happen to share a fingerprint will be collapsed too.
+    ///
+    /// ```rust
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::{Hash, Hasher};
+    ///
+    /// use frozenset::FrozenSet;
+    ///
+    /// let normalised = |s: &&str| {
+    ///     let mut hasher = DefaultHasher::new();
+    ///     s.trim().to_lowercase().hash(&mut hasher);
+    ///     hasher.finish()
+    /// };
+    /// let words = ["Cat", " cat", "dog"];
+    /// let set = FrozenSet::<_>::from_iter_dedup_by_fingerprint(words, normalised);
+    /// assert_eq!(set, ["Cat", "dog"].into());
+    /// ```
+    #[must_use]
+    pub fn from_iter_dedup_by_fingerprint(
+        iter: impl IntoIterator<Item = T>,
+        fingerprint: impl Fn(&T) -> u64,
+    ) -> Self
+    where
+        T: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let mut seen = HashSet::<u64, S>::default();
+        iter.into_iter()
+            .filter(|v| seen.insert(fingerprint(v)))
+            .collect()
+    }
+
+    /// Iterate over every unordered pair of distinct elements in this set.
+    ///
+    /// Each pair is yielded exactly once, and no element is paired with itself,
+    /// so a set of `n` elements yields `n * (n - 1) / 2` pairs. The order of
+    /// the pairs, and of the elements within each pair, is unspecified.
+    pub fn unordered_pairs(&self) -> impl Iterator<Item = (&T, &T)> {
+        self.set
+            .iter()
+            .enumerate()
+            .flat_map(move |(i, a)| self.set.iter().skip(i + 1).map(move |b| (a, b)))
+    }
+
+    /// Get the smallest element of this set, if it is non-empty.
+    #[must_use]
+    pub fn min(&self) -> Option<&T>
+    where
+        T: Ord,
+    {
+        self.set.iter().min()
+    }
+
+    /// Get the largest element of this set, if it is non-empty.
+    #[must_use]
+    pub fn max(&self) -> Option<&T>
+    where
+        T: Ord,
+    {
+        self.set.iter().max()
+    }
+
+    /// Choose a uniformly-random subset of up to `n` elements of this set.
+    ///
+    /// This uses reservoir sampling, so it makes a single pass over the set
+    /// and only allocates space for the chosen elements. If `n` is at least
+    /// the size of the set, the whole set is returned.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let set: FrozenSet<u32> = (0..10).collect();
+    /// let mut counts = [0; 10];
+    /// for seed in 0..2000 {
+    ///     let sample = set.sample(3, &mut StdRng::seed_from_u64(seed));
+    ///     assert_eq!(sample.len(), 3);
+    ///     assert!(sample.is_subset(&set));
+    ///     for &v in sample.iter() {
+    ///         counts[v as usize] += 1;
+    ///     }
+    /// }
+    /// // Each element is expected to be chosen 2000 * 3 / 10 = 600 times.
+    /// assert!(counts.iter().all(|&count| (450..750).contains(&count)));
+    ///
+    /// let mut rng = StdRng::seed_from_u64(0);
+    /// assert_eq!(set.sample(10, &mut rng), set);
+    /// assert_eq!(set.sample(20, &mut rng), set);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[must_use]
+    pub fn sample<R: rand::Rng + ?Sized>(&self, n: usize, rng: &mut R) -> Self
+    where
+        T: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        if n >= self.set.len() {
+            return self.set.iter().cloned().collect();
+        }
+        let mut reservoir = Vec::with_capacity(n);
+        for (i, v) in self.set.iter().enumerate() {
+            if i < n {
+                reservoir.push(v);
+            } else {
+                let j = rng.gen_range(0..=i);
+                if j < n {
+                    reservoir[j] = v;
+                }
+            }
+        }
+        reservoir.into_iter().cloned().collect()
+    }
+
+    /// Pack the membership of this set into a bitmask, with the bit for each
+    /// element (as given by [`BitmaskElement::bit_index`]) set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any element's bit index is 64 or more.
+    #[must_use]
+    pub fn to_bitmask(&self) -> u64
+    where
+        T: BitmaskElement,
+    {
+        self.set.iter().fold(0, |mask, v| {
+            let index = v.bit_index();
+            assert!(
+                index < u64::BITS,
+                "bit index {index} does not fit in a 64-bit mask",
+            );
+            mask | (1 << index)
+        })
+    }
+
+    /// Create a new `FrozenSet` from a bitmask produced by
+    /// [`to_bitmask`](Self::to_bitmask).
+    ///
+    /// Bits which do not represent any value are ignored.
+    #[must_use]
+    pub fn from_bitmask(mask: u64) -> Self
+    where
+        T: BitmaskElement + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        (0..u64::BITS)
+            .filter(|i| mask & (1 << i) != 0)
+            .filter_map(T::from_bit_index)
+            .collect()
+    }
+
+    /// Check whether this set has no elements in common with `other`, which
+    /// may use a different hasher.
+    ///
+    /// This iterates over the smaller of the two sets, stopping at the first
+    /// common element.
+    ///
+    /// ```rust
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    ///
+    /// use frozenset::FrozenSet;
+    ///
+    /// type Other = FrozenSet<u32, BuildHasherDefault<DefaultHasher>>;
+    /// let small: FrozenSet<u32> = [1, 2].into();
+    /// let overlapping: Other = [2, 3, 4, 5].into_iter().collect();
+    /// let disjoint: Other = [6, 7, 8].into_iter().collect();
+    /// assert!(!small.disjoint_from(&overlapping));
+    /// assert!(!overlapping.disjoint_from(&small));
+    /// assert!(small.disjoint_from(&disjoint));
+    /// assert!(disjoint.disjoint_from(&small));
+    /// ```
+    #[must_use]
+    pub fn disjoint_from<S2: BuildHasher>(&self, other: &FrozenSet<T, S2>) -> bool
+    where
+        T: Eq + Hash,
+        S: BuildHasher,
+    {
+        if self.set.len() <= other.set.len() {
+            self.set.iter().all(|v| !other.set.contains(v))
+        } else {
+            other.set.iter().all(|v| !self.set.contains(v))
+        }
+    }
+
+    /// Check whether every item yielded by `iter` is in this set, stopping at
+    /// the first one which is not.
+    ///
+    /// This avoids building a temporary set just to call
+    /// [`is_superset`](HashSet::is_superset).
+    #[must_use]
+    pub fn is_superset_of_iter(&self, iter: impl IntoIterator<Item = T>) -> bool
+    where
+        T: Eq + Hash,
+        S: BuildHasher,
+    {
+        iter.into_iter().all(|v| self.set.contains(&v))
+    }
+
+    /// Check whether every borrowed item yielded by `iter` is in this set,
+    /// stopping at the first one which is not.
+    ///
+    /// This is like [`is_superset_of_iter`](Self::is_superset_of_iter), but
+    /// takes items by reference, in any form `T` can be borrowed as.
+    #[must_use]
+    pub fn contains_all<'a, Q: Eq + Hash + ?Sized + 'a>(
+        &self,
+        iter: impl IntoIterator<Item = &'a Q>,
+    ) -> bool
+    where
+        T: Eq + Hash + Borrow<Q>,
+        S: BuildHasher,
+    {
+        iter.into_iter().all(|v| self.set.contains(v))
+    }
+
+    /// Find the element of this set closest to `target`, according to the
+    /// distance function `dist`.
+    ///
+    /// This is a linear scan, taking O(n) calls to `dist`. If several elements
+    /// are equally close, which of them is returned is unspecified.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let set: FrozenSet<i32> = [1, 5, 10].into();
+    /// assert_eq!(set.closest_to(&7, |a, b| (a - b).abs()), Some(&5));
+    /// ```
+    #[must_use]
+    pub fn closest_to<D: Ord>(
+        &self,
+        target: &T,
+        dist: impl Fn(&T, &T) -> D,
+    ) -> Option<&T> {
+        self.set.iter().min_by_key(|v| dist(v, target))
+    }
+
+    /// Get the `n` smallest elements of this set.
+    ///
+    /// This keeps a bounded heap of the best candidates seen so far, so takes
+    /// O(m log n) time for a set of `m` elements. As the elements of a set are
+    /// distinct, there are no ties to break (unless `T`'s [`Ord`] disagrees
+    /// with its [`Eq`]). If `n` is at least the size of the set, the whole set
+    /// is returned.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let set: FrozenSet<u32> = [5, 1, 4, 2, 3].into();
+    /// assert_eq!(set.smallest_n(2), [1, 2].into());
+    /// assert_eq!(set.largest_n(2), [4, 5].into());
+    /// assert_eq!(set.smallest_n(10), set);
+    /// assert_eq!(set.largest_n(usize::MAX), set);
+    /// ```
+    #[must_use]
+    pub fn smallest_n(&self, n: usize) -> Self
+    where
+        T: Ord + Clone + Hash,
+        S: BuildHasher + Default,
+    {
+        let mut heap = BinaryHeap::with_capacity(n.min(self.set.len()) + 1);
+        for v in &self.set {
+            heap.push(v);
+            if heap.len() > n {
+                heap.pop();
+            }
+        }
+        heap.into_iter().cloned().collect()
+    }
+
+    /// Get the `n` largest elements of this set.
+    ///
+    /// This is the counterpart to [`smallest_n`](Self::smallest_n), with the
+    /// same complexity.
+    #[must_use]
+    pub fn largest_n(&self, n: usize) -> Self
+    where
+        T: Ord + Clone + Hash,
+        S: BuildHasher + Default,
+    {
+        let mut heap = BinaryHeap::with_capacity(n.min(self.set.len()) + 1);
+        for v in &self.set {
+            heap.push(Reverse(v));
+            if heap.len() > n {
+                heap.pop();
+            }
+        }
+        heap.into_iter().map(|Reverse(v)| v.clone()).collect()
+    }
+
+    /// Get the Pareto front of this set: the elements which are not dominated
+    /// by any other element, where larger objective values are better.
+    ///
+    /// An element is dominated if another element is at least as good on every
+    /// objective, and strictly better on at least one. Objective values which
+    /// are incomparable (such as `NaN`s) count as neither at least as good nor
+    /// better, so they never contribute to domination; elements with identical
+    /// objectives do not dominate each other, and so are all kept.
+    ///
+    /// `objectives` is called once per element, but every pair of elements is
+    /// then compared, so this takes O(n²) time.
+    #[must_use]
+    pub fn pareto_front<D: PartialOrd>(
+        &self,
+        objectives: impl Fn(&T) -> Vec<D>,
+    ) -> Self
+    where
+        T: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        fn dominates<D: PartialOrd>(a: &[D], b: &[D]) -> bool {
+            a.iter().zip(b).all(|(a, b)| a >= b)
+                && a.iter().zip(b).any(|(a, b)| a > b)
+        }
+        let scored: Vec<_> = self.set.iter().map(|v| (v, objectives(v))).collect();
+        scored
+            .iter()
+            .filter(|(_, a)| !scored.iter().any(|(_, b)| dominates(b, a)))
+            .map(|(v, _)| (*v).clone())
+            .collect()
+    }
+
+    /// Transform the elements of this set with `f`, stopping at the first
+    /// element for which it returns `None`.
+    ///
+    /// Note that the iteration order of a set is unspecified, so if `f` returns
+    /// `None` for any element, which other elements have already been
+    /// transformed (and so end up in the result) is also unspecified.
+    #[must_use]
+    pub fn map_while<U: Eq + Hash>(
+        self,
+        f: impl FnMut(T) -> Option<U>,
+    ) -> FrozenSet<U, S>
+    where
+        S: BuildHasher + Default,
+    {
+        self.set.into_iter().map_while(f).collect()
+    }
+
+    /// Copy the elements of this set into a sorted [`BTreeSet`].
+    ///
+    /// Building the tree sorts the elements, taking O(n log n) time.
+    #[must_use]
+    pub fn to_btreeset(&self) -> BTreeSet<T>
+    where
+        T: Ord + Clone,
+    {
+        self.set.iter().cloned().collect()
+    }
+
+    /// Convert this set into a sorted [`BTreeSet`].
+    ///
+    /// Building the tree sorts the elements, taking O(n log n) time.
+    #[must_use]
+    pub fn into_btreeset(self) -> BTreeSet<T>
+    where
+        T: Ord,
+    {
+        self.set.into_iter().collect()
+    }
+
+    /// Check whether this set has no elements in common with any of `others`,
+    /// stopping at the first overlap.
+    ///
+    /// Each comparison iterates over the smaller of the two sets, as
+    /// [`disjoint_from`](Self::disjoint_from) does.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let set: FrozenSet<u32> = [1, 2, 3].into();
+    /// let disjoint: [FrozenSet<u32>; 2] = [[4, 5].into(), [6].into()];
+    /// assert!(set.disjoint_from_all(&disjoint));
+    /// let mixed: [FrozenSet<u32>; 3] = [[4].into(), [3, 7].into(), [8].into()];
+    /// assert!(!set.disjoint_from_all(&mixed));
+    /// assert!(set.disjoint_from_all(&[]));
+    /// ```
+    #[must_use]
+    pub fn disjoint_from_all(&self, others: &[Self]) -> bool
+    where
+        T: Eq + Hash,
+        S: BuildHasher,
+    {
+        others.iter().all(|other| self.disjoint_from(other))
+    }
+
+    /// Get a lazy view of the union of this set and `other`, without building
+    /// a new set.
+    #[must_use]
+    pub fn chain<'a>(&'a self, other: &'a Self) -> FrozenSetChain<'a, T, S> {
+        FrozenSetChain {
+            first: self,
+            second: other,
+        }
+    }
+
+    /// Get the elements of this set which lie within `range`.
+    ///
+    /// As a `FrozenSet` is unordered, this is a linear scan over every element.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let set: FrozenSet<i32> = (0..10).collect();
+    /// assert_eq!(set.in_range(3..6), [3, 4, 5].into());
+    /// assert_eq!(set.in_range(3..=6), [3, 4, 5, 6].into());
+    /// assert_eq!(set.in_range(..2), [0, 1].into());
+    /// ```
+    #[must_use]
+    pub fn in_range(&self, range: impl RangeBounds<T>) -> Self
+    where
+        T: Ord + Clone + Hash,
+        S: BuildHasher + Default,
+    {
+        self.set
+            .iter()
+            .filter(|v| range.contains(*v))
+            .cloned()
+            .collect()
+    }
+
+    /// Expand each element of this set into any number of new elements with
+    /// `each`, collecting all of them into a new set.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let set: FrozenSet<u32> = [4, 6].into();
+    /// let divisors = set.flat_map_with(|&n| (1..=n).filter(move |d| n % d == 0));
+    /// assert_eq!(divisors, [1, 2, 3, 4, 6].into());
+    /// ```
+    #[must_use]
+    pub fn flat_map_with<U: Eq + Hash, I: IntoIterator<Item = U>>(
+        &self,
+        each: impl Fn(&T) -> I,
+    ) -> FrozenSet<U, S>
+    where
+        S: BuildHasher + Default,
+    {
+        self.set.iter().flat_map(each).collect()
+    }
+
+    /// Compute the hash that the union of this set and `other` would have,
+    /// without building the union.
+    ///
+    /// The result is the same as hashing the union into a new
+    /// [`DefaultHasher`]. Elements of `other` which are also in this set are
+    /// only counted once, so this costs one lookup per element of `other`.
+    ///
+    /// ```rust
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::{BuildHasher, BuildHasherDefault};
+    ///
+    /// use frozenset::FrozenSet;
+    ///
+    /// let a: FrozenSet<i32> = [1, 2, 3].into();
+    /// let b: FrozenSet<i32> = [3, 4].into();
+    /// let union: FrozenSet<i32> = a.union(&b).copied().collect();
+    /// let hasher = BuildHasherDefault::<DefaultHasher>::default();
+    /// assert_eq!(a.combined_hash_with(&b), hasher.hash_one(&union));
+    /// ```
+    #[must_use]
+    pub fn combined_hash_with<S2>(&self, other: &FrozenSet<T, S2>) -> u64
+    where
+        T: Eq + Hash,
+        S: BuildHasher,
+    {
+        let only_other = other.set.iter().filter(|v| !self.set.contains(*v));
+        element_hash(&combined_element_hash(self.set.iter().chain(only_other)))
+    }
+
+    /// Count, for each element of any of `sets`, how many of the sets it is
+    /// in.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let tags: [FrozenSet<&str>; 3] = [["rust"].into(), ["rust", "go"].into(), ["go"].into()];
+    /// let counts = FrozenSet::element_frequencies(&tags);
+    /// assert_eq!(counts, [("rust", 2), ("go", 2)].into());
+    /// ```
+    #[must_use]
+    pub fn element_frequencies(sets: &[Self]) -> FrozenMap<T, usize, S>
+    where
+        T: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let mut counts = HashMap::<T, usize, S>::default();
+        for v in sets.iter().flat_map(|set| &set.set) {
+            *counts.entry(v.clone()).or_insert(0) += 1;
+        }
+        counts.freeze()
+    }
+
+    /// Check whether at least `n` elements of this set satisfy `pred`,
+    /// stopping as soon as `n` matches have been found.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let set: FrozenSet<u32> = (1..=10).collect();
+    /// let even = |v: &u32| v % 2 == 0;
+    /// assert!(set.at_least_n_match(4, even));
+    /// assert!(set.at_least_n_match(5, even));
+    /// assert!(!set.at_least_n_match(6, even));
+    /// assert!(set.at_least_n_match(0, |_| false));
+    /// assert!(FrozenSet::<u32>::new().at_least_n_match(0, even));
+    /// ```
+    #[must_use]
+    pub fn at_least_n_match(&self, n: usize, pred: impl Fn(&T) -> bool) -> bool {
+        self.set.iter().filter(|v| pred(v)).take(n).count() == n
+    }
+
+    /// Get the elements of this set in ascending order, each paired with its
+    /// 0-based rank.
+    ///
+    /// This clones the elements into a new `Vec` and sorts it.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let set: FrozenSet<char> = ['c', 'a', 'd', 'b'].into();
+    /// assert_eq!(set.ranked(), vec![('a', 0), ('b', 1), ('c', 2), ('d', 3)]);
+    /// let ranks: Vec<usize> = set.ranked().into_iter().map(|(_, rank)| rank).collect();
+    /// assert_eq!(ranks, (0..set.len()).collect::<Vec<_>>());
+    ///
+    /// assert_eq!(FrozenSet::<char>::new().ranked(), vec![]);
+    /// ```
+    #[must_use]
+    pub fn ranked(&self) -> Vec<(T, usize)>
+    where
+        T: Ord + Clone,
+    {
+        let elements = self.sorted_refs();
+        elements
+            .into_iter()
+            .enumerate()
+            .map(|(rank, v)| (v.clone(), rank))
+            .collect()
+    }
+
+    /// Split this set into `n` shards, assigning each element to a shard based
+    /// on its hash.
+    ///
+    /// The assignment uses an unkeyed [`DefaultHasher`] rather than this set's
+    /// hasher, so it is reproducible across processes and runs of a program.
+    /// It is not guaranteed to be stable across Rust versions, however, as the
+    /// standard library may change [`DefaultHasher`]'s algorithm.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let set: FrozenSet<u32> = (0..100).collect();
+    /// let shards = set.shard(4);
+    /// assert_eq!(shards.len(), 4);
+    /// let union: FrozenSet<u32> = shards.iter().flat_map(|shard| shard.iter().copied()).collect();
+    /// assert_eq!(union, set);
+    /// for (i, a) in shards.iter().enumerate() {
+    ///     assert!(shards[i + 1..].iter().all(|b| a.disjoint_from(b)));
+    /// }
+    ///
+    /// let reversed: FrozenSet<u32> = (0..100).rev().collect();
+    /// assert_eq!(set.shard(4), shards);
+    /// assert_eq!(reversed.shard(4), shards);
+    /// ```
+    #[must_use]
+    pub fn shard(&self, n: usize) -> Vec<Self>
+    where
+        T: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        assert!(n > 0, "cannot split a set into zero shards");
+        let mut shards: Vec<HashSet<T, S>> =
+            (0..n).map(|_| HashSet::default()).collect();
+        for v in &self.set {
+            // The remainder is less than `n`, so always fits in a `usize`.
+            let shard = (element_hash(v) % n as u64) as usize;
+            shards[shard].insert(v.clone());
+        }
+        shards.into_iter().map(Freeze::freeze).collect()
+    }
+
+    /// Convert this set into an array of its elements in ascending order, if
+    /// it has exactly `N` elements.
+    ///
+    /// If the set has a different number of elements, it is returned as the
+    /// error.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let set: FrozenSet<i32> = [3, 1, 2].into();
+    /// assert_eq!(set.clone().try_into_array(), Ok([1, 2, 3]));
+    /// assert_eq!(set.clone().try_into_array::<2>(), Err(set));
+    /// ```
+    pub fn try_into_array<const N: usize>(self) -> Result<[T; N], Self>
+    where
+        T: Ord,
+    {
+        if self.set.len() != N {
+            return Err(self);
+        }
+        let mut elements: Vec<_> = self.set.into_iter().collect();
+        elements.sort_unstable();
+        match elements.try_into() {
+            Ok(array) => Ok(array),
+            Err(_) => unreachable!("the set has exactly N elements"),
+        }
+    }
+
+    /// Choose a random element of this set, with probability proportional to
+    /// its `weight`.
+    ///
+    /// Elements with a weight which is not positive (including `NaN`) are
+    /// never chosen; if no element has a positive weight, this returns `None`.
+    /// This uses weighted reservoir sampling, so it makes a single pass over
+    /// the set without allocating.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let set: FrozenSet<&str> = ["rare", "common", "never"].into();
+    /// let weight = |v: &&str| match *v {
+    ///     "rare" => 1.0,
+    ///     "common" => 3.0,
+    ///     _ => 0.0,
+    /// };
+    /// let mut rng = StdRng::seed_from_u64(7);
+    /// let mut common = 0;
+    /// for _ in 0..4000 {
+    ///     match set.weighted_sample(weight, &mut rng) {
+    ///         Some(&"common") => common += 1,
+    ///         Some(&"rare") => {}
+    ///         other => panic!("unexpected sample {other:?}"),
+    ///     }
+    /// }
+    /// // "common" is expected to be chosen 4000 * 3 / 4 = 3000 times.
+    /// assert!((2800..3200).contains(&common));
+    ///
+    /// assert_eq!(set.weighted_sample(|_| 0.0, &mut rng), None);
+    /// assert_eq!(set.weighted_sample(|_| -1.0, &mut rng), None);
+    /// assert_eq!(set.weighted_sample(|_| f64::NAN, &mut rng), None);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[must_use]
+    pub fn weighted_sample<R: rand::Rng + ?Sized>(
+        &self,
+        weight: impl Fn(&T) -> f64,
+        rng: &mut R,
+    ) -> Option<&T> {
+        // Each element gets the key `u^(1/w)` for uniform `u`, and the element
+        // with the largest key is chosen. Comparing `ln(u)/w` instead gives the
+        // same order, without underflowing for small weights.
+        self.set
+            .iter()
+            .map(|v| (v, weight(v)))
+            .filter(|(_, w)| *w > 0.0)
+            .map(|(v, w)| (rng.gen::<f64>().ln() / w, v))
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, v)| v)
+    }
+
+    /// Collapse the elements of this set into the smallest list of inclusive
+    /// ranges covering them, in ascending order.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let set: FrozenSet<u32> = [1, 2, 3, 7, 8, 10].into();
+    /// assert_eq!(set.to_ranges(), vec![1..=3, 7..=8, 10..=10]);
+    /// assert_eq!(FrozenSet::<_>::from_ranges(set.to_ranges()), set);
+    /// ```
+    #[must_use]
+    pub fn to_ranges(&self) -> Vec<RangeInclusive<T>>
+    where
+        T: Ord + Clone + Successor,
+    {
+        let elements = self.sorted_refs();
+        let mut ranges: Vec<RangeInclusive<T>> = Vec::new();
+        for v in elements {
+            match ranges.last_mut() {
+                Some(range) if range.end().successor().as_ref() == Some(v) => {
+                    *range = range.start().clone()..=v.clone();
+                }
+                _ => ranges.push(v.clone()..=v.clone()),
+            }
+        }
+        ranges
+    }
+
+    /// Create a new `FrozenSet` containing every value in each of the given
+    /// inclusive ranges, reversing [`to_ranges`](Self::to_ranges).
+    #[must_use]
+    pub fn from_ranges(ranges: impl IntoIterator<Item = RangeInclusive<T>>) -> Self
+    where
+        T: Ord + Clone + Hash + Successor,
+        S: BuildHasher + Default,
+    {
+        let mut set = HashSet::<T, S>::default();
+        for range in ranges {
+            let (start, end) = range.into_inner();
+            let mut next = Some(start);
+            while let Some(v) = next.filter(|v| *v <= end) {
+                next = v.successor();
+                set.insert(v);
+            }
+        }
+        set.freeze()
+    }
+
+    /// Compute the sizes of the pairwise intersections of `sets`, as a matrix
+    /// whose diagonal holds the sizes of the sets themselves.
+    ///
+    /// The matrix is symmetric, so only the upper triangle is computed; each
+    /// intersection is counted by iterating over the smaller of the two sets.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let sets: [FrozenSet<i32>; 3] = [[1, 2, 3].into(), [2, 3].into(), [4].into()];
+    /// let matrix = FrozenSet::overlap_matrix(&sets);
+    /// assert_eq!(matrix, vec![vec![3, 2, 0], vec![2, 2, 0], vec![0, 0, 1]]);
+    /// ```
+    #[must_use]
+    pub fn overlap_matrix(sets: &[Self]) -> Vec<Vec<usize>>
+    where
+        T: Eq + Hash,
+        S: BuildHasher,
+    {
+        let mut matrix = vec![vec![0; sets.len()]; sets.len()];
+        for (i, a) in sets.iter().enumerate() {
+            matrix[i][i] = a.set.len();
+            for (j, b) in sets.iter().enumerate().skip(i + 1) {
+                let (smaller, larger) = if a.set.len() <= b.set.len() {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                let overlap = smaller
+                    .set
+                    .iter()
+                    .filter(|v| larger.set.contains(*v))
+                    .count();
+                matrix[i][j] = overlap;
+                matrix[j][i] = overlap;
+            }
+        }
+        matrix
+    }
+
+    /// Check whether this set is an antichain under the partial order `le`:
+    /// that is, whether no two distinct elements are comparable.
+    ///
+    /// Every pair of elements is compared (in both directions), stopping at
+    /// the first comparable pair, so this takes O(n²) time.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let divides = |a: &u32, b: &u32| b % a == 0;
+    /// assert!(FrozenSet::<u32>::from([2, 3, 5]).is_antichain(divides));
+    /// assert!(!FrozenSet::<u32>::from([2, 4, 8]).is_antichain(divides));
+    /// ```
+    #[must_use]
+    pub fn is_antichain(&self, le: impl Fn(&T, &T) -> bool) -> bool {
+        self.unordered_pairs().all(|(a, b)| !le(a, b) && !le(b, a))
+    }
+
+    /// Get the elements of this set in ascending order, without cloning them.
+    ///
+    /// The returned references borrow from this set. Sorting them takes
+    /// O(n log n) time, and allocates a new `Vec`.
+    #[must_use]
+    pub fn sorted_refs(&self) -> Vec<&T>
+    where
+        T: Ord,
+    {
+        let mut elements: Vec<_> = self.set.iter().collect();
+        elements.sort_unstable();
+        elements
+    }
+
+    /// Call `f` with each combination of `k` distinct elements of this set.
+    ///
+    /// Each combination is passed as a slice of references, which is reused
+    /// between calls, so it is only valid for the duration of each call; this
+    /// avoids allocating a new set for each combination. The order of the
+    /// combinations, and of the elements within each, is unspecified. If `k`
+    /// is larger than the set, `f` is never called.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let set: FrozenSet<i32> = (0..6).collect();
+    /// let mut count = 0;
+    /// set.for_each_combination(3, |combination| {
+    ///     assert_eq!(combination.len(), 3);
+    ///     count += 1;
+    /// });
+    /// assert_eq!(count, 20);
+    /// ```
+    pub fn for_each_combination<R>(&self, k: usize, mut f: impl FnMut(&[&T]) -> R) {
+        let elements: Vec<_> = self.set.iter().collect();
+        let n = elements.len();
+        if k > n {
+            return;
+        }
+        let mut indices: Vec<usize> = (0..k).collect();
+        let mut combination = Vec::with_capacity(k);
+        loop {
+            combination.clear();
+            combination.extend(indices.iter().map(|&i| elements[i]));
+            f(&combination);
+            // Advance to the next combination in lexicographic order of
+            // indices, by bumping the rightmost index which can still move.
+            let Some(i) = (0..k).rev().find(|&i| indices[i] != i + n - k) else {
+                return;
+            };
+            indices[i] += 1;
+            for j in i + 1..k {
+                indices[j] = indices[j - 1] + 1;
+            }
+        }
+    }
+
+    /// Compute a 128-bit checksum of the elements of this set, for telling
+    /// apart large numbers of sets with fewer collisions than their 64-bit
+    /// [`Hash`].
+    ///
+    /// Each element contributes two independent 64-bit hashes, and these are
+    /// summed over the set, so (like [`Hash`]) the result does not depend on
+    /// iteration order. This is not a cryptographic hash.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let a: FrozenSet<&str> = ["x", "y", "z"].into();
+    /// let b: FrozenSet<&str> = ["z", "x", "y"].into();
+    /// let c: FrozenSet<&str> = ["x", "y"].into();
+    /// assert_eq!(a.checksum(), b.checksum());
+    /// assert_ne!(a.checksum(), c.checksum());
+    /// ```
+    #[must_use]
+    pub fn checksum(&self) -> u128
+    where
+        T: Hash,
+    {
+        self.set.iter().fold(0u128, |sum, v| {
+            let high = u128::from(element_hash(v));
+            let low = u128::from(element_hash(&(1u8, v)));
+            sum.wrapping_add(high << 64 | low)
+        })
+    }
+
+    /// Get the maximal elements of this set under the partial order `le`: the
+    /// elements which are not below any other element.
+    ///
+    /// Each element is compared with the others until one is found above it,
+    /// so this takes O(n²) time in the worst case.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let divides = |a: &u32, b: &u32| b % a == 0;
+    /// let poset: FrozenSet<u32> = [1, 2, 3, 4, 6, 7].into();
+    /// assert_eq!(poset.maximal_elements(divides), [4, 6, 7].into());
+    /// assert_eq!(poset.minimal_elements(divides), [1].into());
+    /// ```
+    #[must_use]
+    pub fn maximal_elements(&self, le: impl Fn(&T, &T) -> bool) -> FrozenSet<T, S>
+    where
+        T: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        self.set
+            .iter()
+            .filter(|a| !self.set.iter().any(|b| a != &b && le(a, b)))
+            .cloned()
+            .collect()
+    }
+
+    /// Get the minimal elements of this set under the partial order `le`: the
+    /// elements which are not above any other element.
+    ///
+    /// This is the counterpart to [`maximal_elements`](Self::maximal_elements),
+    /// with the same complexity.
+    #[must_use]
+    pub fn minimal_elements(&self, le: impl Fn(&T, &T) -> bool) -> FrozenSet<T, S>
+    where
+        T: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        self.maximal_elements(|a, b| le(b, a))
+    }
+
+    /// Compute a cache key for this set together with `context` (such as a
+    /// version number or tenant ID), without building a composite key.
+    ///
+    /// This is the same as hashing the tuple `(self, context)` with a new
+    /// [`DefaultHasher`], so it does not depend on iteration order, and is
+    /// reproducible within a build of the program (but not necessarily across
+    /// Rust versions).
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let features: FrozenSet<&str> = ["dark-mode", "beta"].into();
+    /// let reordered: FrozenSet<&str> = ["beta", "dark-mode"].into();
+    /// assert_eq!(features.cache_key_with(&1u32), reordered.cache_key_with(&1u32));
+    /// assert_ne!(features.cache_key_with(&1u32), features.cache_key_with(&2u32));
+    /// ```
+    #[must_use]
+    pub fn cache_key_with(&self, context: &impl Hash) -> u64
+    where
+        T: Hash,
+    {
+        element_hash(&(self, context))
+    }
+}
+impl<T, S> FrozenSet<(T, T), S> {
+    /// Compute the transitive closure of this set, viewed as a binary relation
+    /// of `(from, to)` pairs.
+    ///
+    /// The result contains `(a, c)` whenever `c` is reachable from `a` by
+    /// following one or more pairs of this relation.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// let edges: FrozenSet<(u8, u8)> = [(1, 2), (2, 3)].into();
+    /// let reachable: FrozenSet<(u8, u8)> = [(1, 2), (2, 3), (1, 3)].into();
+    /// assert_eq!(edges.transitive_closure_pairs(), reachable);
+    /// ```
+    #[must_use]
+    pub fn transitive_closure_pairs(&self) -> FrozenSet<(T, T), S>
+    where
+        T: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let mut nodes = Vec::new();
+        let mut index = HashMap::<&T, usize, S>::default();
+        for (a, b) in &self.set {
+            for node in [a, b] {
+                index.entry(node).or_insert_with(|| {
+                    nodes.push(node);
+                    nodes.len() - 1
+                });
+            }
+        }
+        let n = nodes.len();
+        let mut reach = vec![false; n * n];
+        for (a, b) in &self.set {
+            reach[index[a] * n + index[b]] = true;
+        }
+        for k in 0..n {
+            for i in 0..n {
+                if reach[i * n + k] {
+                    for j in 0..n {
+                        if reach[k * n + j] {
+                            reach[i * n + j] = true;
+                        }
+                    }
+                }
+            }
+        }
+        (0..n * n)
+            .filter(|&ij| reach[ij])
+            .map(|ij| (nodes[ij / n].clone(), nodes[ij % n].clone()))
+            .collect()
+    }
+
+    /// Compute the reflexive-transitive closure of this set, viewed as a
+    /// binary relation over `domain`.
+    ///
+    /// This is the [transitive
+    /// closure](FrozenSet::transitive_closure_pairs) plus `(x, x)` for every
+    /// `x` in `domain`.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    ///
+    /// // 1 -> 2 -> 3 -> 1 is a cycle, so every node reaches every node.
+    /// let cycle: FrozenSet<(u8, u8)> = [(1, 2), (2, 3), (3, 1)].into();
+    /// let complete: FrozenSet<(u8, u8)> =
+    ///     (1..=3).flat_map(|a| (1..=3).map(move |b| (a, b))).collect();
+    /// assert_eq!(cycle.transitive_closure_pairs(), complete);
+    ///
+    /// // 4 appears in no pair, but still relates to itself.
+    /// let edges: FrozenSet<(u8, u8)> = [(1, 2)].into();
+    /// let domain: FrozenSet<u8> = [1, 2, 4].into();
+    /// let closure = edges.reflexive_transitive_closure(&domain);
+    /// assert_eq!(closure, [(1, 2), (1, 1), (2, 2), (4, 4)].into());
+    /// ```
+    #[must_use]
+    pub fn reflexive_transitive_closure<S2>(
+        &self,
+        domain: &FrozenSet<T, S2>,
+    ) -> FrozenSet<(T, T), S>
+    where
+        T: Clone + Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        let closure = self.transitive_closure_pairs();
+        closure
+            .set
+            .into_iter()
+            .chain(domain.set.iter().map(|x| (x.clone(), x.clone())))
+            .collect()
+    }
+}
+impl<T, S> Deref for FrozenSet<T, S> {
+    type Target = HashSet<T, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.set
+    }
+}
+impl<T, S: Default> Default for FrozenSet<T, S> {
+    fn default() -> Self {
+        Self {
+            set: HashSet::default(),
+        }
+    }
+}
+impl<T, F, S> From<F> for FrozenSet<T, S>
+where
+    HashSet<T, S>: From<F>,
+{
+    fn from(set: F) -> Self {
+        Self {
+            set: set.into(),
+        }
+    }
+}
+impl<T: Eq + Hash, S: BuildHasher + Default> FromIterator<T> for FrozenSet<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            set: iter.into_iter().collect(),
+        }
+    }
+}
+impl<T, S> IntoIterator for FrozenSet<T, S> {
+    type IntoIter = hash_set::IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.set.into_iter()
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher> PartialEq for FrozenSet<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.set.eq(&other.set)
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher> Eq for FrozenSet<T, S> {
+}
+impl<T: UnwindSafe, S: UnwindSafe> UnwindSafe for FrozenSet<T, S> {
+}
+/// The hash of a `FrozenSet` depends only on its elements, so it is the same
+/// for any two equal sets, including a set and its clone. The other types
+/// which compare equal to a `FrozenSet` hash the same as it, too:
+/// ```rust
+/// use std::collections::hash_map::RandomState;
+/// use std::hash::BuildHasher;
+///
+/// use frozenset::FrozenSet;
+///
+/// let hasher = RandomState::new();
+/// let set: FrozenSet<i32> = (0..100).collect();
+/// let reversed: FrozenSet<i32> = (0..100).rev().collect();
+/// assert_eq!(hasher.hash_one(&set), hasher.hash_one(set.clone()));
+/// assert_eq!(hasher.hash_one(&set), hasher.hash_one(&reversed));
+///
+/// let low: FrozenSet<i32> = (0..60).collect();
+/// let high: FrozenSet<i32> = (40..100).collect();
+/// assert_eq!(hasher.hash_one(low.chain(&high)), hasher.hash_one(&set));
+///
+/// #[cfg(feature = "smallvec")]
+/// {
+///     let small: frozenset::FrozenSmallSet<i32, 100> = set.clone().into();
+///     assert_eq!(hasher.hash_one(&small), hasher.hash_one(&set));
+/// }
+/// ```
+impl<T: Hash, S> Hash for FrozenSet<T, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        combined_element_hash(&self.set).hash(state);
+    }
+}
+
+/// A `FrozenSetChain` is a lazy view of the union of two [`FrozenSet`]s,
+/// created by [`FrozenSet::chain`].
+///
+/// Membership tests check both sets, and iteration yields each element of the
+/// union exactly once. To do so, each element of the second set is looked up
+/// in the first and skipped if present, so iterating (or computing the
+/// [`len`](Self::len)) costs one extra lookup per element of the second set.
+///
+/// A `FrozenSetChain` hashes the same as the materialized union (see the
+/// [`Hash`] impl of [`FrozenSet`]), and compares equal to it when on the left
+/// of `==` (a `PartialEq` impl for `FrozenSet` itself would break type
+/// inference for comparisons like `set == x.into()`):
+/// ```rust
+/// use frozenset::FrozenSet;
+///
+/// let a: FrozenSet<i32> = [1, 2, 3].into();
+/// let b: FrozenSet<i32> = [3, 4].into();
+/// let chain = a.chain(&b);
+/// assert!(chain.contains(&1) && chain.contains(&4) && !chain.contains(&5));
+///
+/// let mut elements: Vec<i32> = chain.iter().copied().collect();
+/// elements.sort();
+/// assert_eq!(elements, [1, 2, 3, 4]);
+/// assert_eq!(chain.len(), 4);
+///
+/// assert!(chain == chain.to_frozen_set());
+/// ```
+#[derive(Debug)]
+pub struct FrozenSetChain<'a, T, S = RandomState> {
+    first: &'a FrozenSet<T, S>,
+    second: &'a FrozenSet<T, S>,
+}
+impl<'a, T: Eq + Hash, S: BuildHasher> FrozenSetChain<'a, T, S> {
+    /// Check whether either set contains `value`.
+    #[must_use]
+    pub fn contains<Q: Eq + Hash + ?Sized>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.first.set.contains(value) || self.second.set.contains(value)
+    }
+
+    /// Iterate over the elements of the union, in an unspecified order.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + 'a {
+        let first = self.first;
+        first.set.iter().chain(
+            self.second
+                .set
+                .iter()
+                .filter(move |v| !first.set.contains(*v)),
+        )
+    }
+
+    /// Get the number of elements in the union.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Check whether the union contains no elements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.first.set.is_empty() && self.second.set.is_empty()
+    }
+
+    /// Materialize the union as a new `FrozenSet`.
+    #[must_use]
+    pub fn to_frozen_set(&self) -> FrozenSet<T, S>
+    where
+        T: Clone,
+        S: Default,
+    {
+        self.iter().cloned().collect()
+    }
+}
+impl<T, S> Clone for FrozenSetChain<'_, T, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T, S> Copy for FrozenSetChain<'_, T, S> {
+}
+impl<T: Eq + Hash, S: BuildHasher> PartialEq for FrozenSetChain<'_, T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|v| other.contains(v))
+    }
+}
+impl<T: Eq + Hash, S: BuildHasher> Eq for FrozenSetChain<'_, T, S> {
+}
+impl<T: Eq + Hash, S: BuildHasher> PartialEq<FrozenSet<T, S>>
+    for FrozenSetChain<'_, T, S>
+{
+    fn eq(&self, other: &FrozenSet<T, S>) -> bool {
+        self.len() == other.len() && self.iter().all(|v| other.contains(v))
+    }
+}
+impl<T: Eq + Hash, S: BuildHasher> Hash for FrozenSetChain<'_, T, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        combined_element_hash(self.iter()).hash(state);
+    }
+}
+
+/// A `StreamingFrozenSetBuilder` builds a [`FrozenSet`] from a stream of
+/// elements which may contain many duplicates.
+///
+/// Elements pushed by reference with [`push_ref`](Self::push_ref) are only
+/// converted to owned values if they have not been seen before, so duplicates
+/// cost a lookup but no allocation.
+///
+/// ```rust
+/// use frozenset::StreamingFrozenSetBuilder;
+///
+/// let mut builder = StreamingFrozenSetBuilder::<String>::new();
+/// for word in "the cat sat on the mat".split(' ') {
+///     builder.push_ref(word);
+/// }
+/// assert_eq!(builder.build().len(), 5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StreamingFrozenSetBuilder<T, S = RandomState> {
+    set: HashSet<T, S>,
+}
+impl<T> StreamingFrozenSetBuilder<T, RandomState> {
+    /// Create a new empty `StreamingFrozenSetBuilder` with the default hasher.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+impl<T: Eq + Hash, S: BuildHasher> StreamingFrozenSetBuilder<T, S> {
+    /// Add `value` to the set being built, returning whether it was new.
+    pub fn push(&mut self, value: T) -> bool {
+        self.set.insert(value)
+    }
+
+    /// Add an owned copy of `value` to the set being built, if it was new,
+    /// returning whether it was.
+    pub fn push_ref<Q: Eq + Hash + ToOwned<Owned = T> + ?Sized>(
+        &mut self,
+        value: &Q,
+    ) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        if self.set.contains(value) {
+            false
+        } else {
+            self.set.insert(value.to_owned())
+        }
+    }
+
+    /// Get the number of distinct elements pushed so far.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Check whether no elements have been pushed yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Finish building, returning the `FrozenSet` of every distinct element
+    /// pushed.
+    #[must_use]
+    pub fn build(self) -> FrozenSet<T, S> {
+        self.set.freeze()
+    }
+}
+impl<T, S: Default> Default for StreamingFrozenSetBuilder<T, S> {
+    fn default() -> Self {
+        Self {
+            set: HashSet::default(),
+        }
+    }
+}
+impl<T: Eq + Hash, S: BuildHasher> Extend<T> for StreamingFrozenSetBuilder<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.set.extend(iter);
+    }
+}
+
+/// A `FrozenBiMap` is an immutable one-to-one mapping between left and right
+/// values, which can be looked up in either direction.
+///
+/// It is stored as a pair of [`FrozenMap`]s, one in each direction, and like
+/// them it implements [`Hash`] so it can be used as a key in other maps and
+/// sets.
+#[derive(Debug, Clone)]
+pub struct FrozenBiMap<L, R, S = RandomState> {
+    left_to_right: FrozenMap<L, R, S>,
+    right_to_left: FrozenMap<R, L, S>,
+}
+impl<L, R> FrozenBiMap<L, R, RandomState> {
+    /// Create a new empty `FrozenBiMap` with the default hasher.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+impl<L, R, S> FrozenBiMap<L, R, S> {
+    /// Create a new `FrozenBiMap` from an iterator of `(left, right)` pairs.
+    ///
+    /// Every left value and every right value must be unique; if a pair
+    /// repeats a left or right value seen earlier, that pair is returned as
+    /// the error.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenBiMap;
+    ///
+    /// let bimap = FrozenBiMap::<_, _>::try_from_iter([(1, 'a'), (2, 'b')]).unwrap();
+    /// assert_eq!(bimap.get_by_left(&2), Some(&'b'));
+    /// assert_eq!(bimap.get_by_right(&'a'), Some(&1));
+    ///
+    /// let duplicate = FrozenBiMap::<_, _>::try_from_iter([(1, 'a'), (2, 'a')]);
+    /// assert_eq!(duplicate.unwrap_err(), (2, 'a'));
+    /// ```
+    pub fn try_from_iter(
+        iter: impl IntoIterator<Item = (L, R)>,
+    ) -> Result<Self, (L, R)>
+    where
+        L: Eq + Hash + Clone,
+        R: Eq + Hash + Clone,
+        S: BuildHasher + Default,
+    {
+        let mut left_to_right = HashMap::<L, R, S>::default();
+        let mut right_to_left = HashMap::<R, L, S>::default();
+        for (left, right) in iter {
+            if left_to_right.contains_key(&left)
+                || right_to_left.contains_key(&right)
+            {
+                return Err((left, right));
+            }
+            left_to_right.insert(left.clone(), right.clone());
+            right_to_left.insert(right, left);
+        }
+        Ok(Self {
+            left_to_right: left_to_right.freeze(),
+            right_to_left: right_to_left.freeze(),
+        })
+    }
+
+    /// Get the right value paired with the given left value.
+    #[must_use]
+    pub fn get_by_left<Q: Eq + Hash + ?Sized>(&self, left: &Q) -> Option<&R>
+    where
+        L: Eq + Hash + Borrow<Q>,
+        S: BuildHasher,
+    {
+        self.left_to_right.get(left)
+    }
+
+    /// Get the left value paired with the given right value.
+    #[must_use]
+    pub fn get_by_right<Q: Eq + Hash + ?Sized>(&self, right: &Q) -> Option<&L>
+    where
+        R: Eq + Hash + Borrow<Q>,
+        S: BuildHasher,
+    {
+        self.right_to_left.get(right)
+    }
+
+    /// Get the number of pairs in this `FrozenBiMap`.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.left_to_right.len()
+    }
+
+    /// Check whether this `FrozenBiMap` contains no pairs.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.left_to_right.is_empty()
     }
-}
-impl<T, S> Freeze for HashSet<T, S> {
-    type Frozen = FrozenSet<T, S>;
 
-    fn freeze(self) -> Self::Frozen {
-        FrozenSet {
-            set: self,
+    /// Get the mapping from left values to right values.
+    #[must_use]
+    pub fn left_to_right(&self) -> &FrozenMap<L, R, S> {
+        &self.left_to_right
+    }
+
+    /// Get the mapping from right values to left values.
+    #[must_use]
+    pub fn right_to_left(&self) -> &FrozenMap<R, L, S> {
+        &self.right_to_left
+    }
+}
+impl<L, R, S: BuildHasher + Default> Default for FrozenBiMap<L, R, S> {
+    fn default() -> Self {
+        Self {
+            left_to_right: FrozenMap::default(),
+            right_to_left: FrozenMap::default(),
         }
     }
 }
+impl<L: Hash + Eq, R: Hash + Eq, S: BuildHasher> PartialEq for FrozenBiMap<L, R, S> {
+    fn eq(&self, other: &Self) -> bool {
+        // The reverse mapping is determined entirely by the forward one.
+        self.left_to_right.eq(&other.left_to_right)
+    }
+}
+impl<L: Hash + Eq, R: Hash + Eq, S: BuildHasher> Eq for FrozenBiMap<L, R, S> {
+}
+impl<L: Hash, R: Hash, S> Hash for FrozenBiMap<L, R, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.left_to_right.hash(state);
+    }
+}
 
-/// A `FrozenMap` is a wrapper around a [`HashMap`] that implements [`Hash`].
+/// A `FrozenSmallSet` is an immutable set which stores up to `N` elements
+/// inline, without allocating, and looks elements up by linear search.
 ///
-/// It is a logic error to mutate any element of the map (via internal
-/// mutability) after it has been frozen.
+/// For sets which are usually tiny, this avoids the overhead of a [`HashSet`];
+/// past `N` elements the set spills onto the heap, but lookups remain linear,
+/// so [`FrozenSet`] is better for larger sets.
 ///
-/// For convenience, `FrozenMap` implements all of [`HashMap`]'s traits, and
-/// will [`Deref`] to [`HashMap`], so you can use it as a drop-in replacement
-/// for an `&HashMap`.
+/// A `FrozenSmallSet` hashes identically to a [`FrozenSet`] with the same
+/// elements (see the [`Hash`] impl of [`FrozenSet`]), and compares equal to it
+/// when on the left of `==`:
+/// ```rust
+/// use frozenset::{FrozenSet, FrozenSmallSet};
+///
+/// let set: FrozenSet<u8> = [1, 2, 3].into();
+/// let small: FrozenSmallSet<u8, 4> = set.clone().into();
+/// assert!(small == set);
+/// assert!(!small.spilled());
+/// assert_eq!(FrozenSet::<u8>::from(small.clone()), set);
+/// assert_eq!(FrozenSmallSet::from(FrozenSet::<u8>::from(small.clone())), small);
+///
+/// let spilled: FrozenSmallSet<u8, 4> = (0..5).collect();
+/// assert!(spilled.spilled());
+/// ```
+#[cfg(feature = "smallvec")]
 #[derive(Debug, Clone)]
-pub struct FrozenMap<K, V, S = RandomState> {
-    map: HashMap<K, V, S>,
+pub struct FrozenSmallSet<T, const N: usize> {
+    elements: smallvec::SmallVec<[T; N]>,
 }
-impl<K, V> FrozenMap<K, V, RandomState> {
-    /// Create a new empty `FrozenMap` with the default hasher.
+#[cfg(feature = "smallvec")]
+impl<T, const N: usize> FrozenSmallSet<T, N> {
+    /// Create a new empty `FrozenSmallSet`.
     #[must_use]
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Un-freeze this `FrozenMap`, returning the underlying [`HashMap`].
+    /// Check whether this set contains `value`.
     #[must_use]
-    pub fn thaw(self) -> HashMap<K, V> {
-        self.map
+    pub fn contains<Q: Eq + ?Sized>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.elements.iter().any(|v| v.borrow() == value)
     }
-}
-impl<K, V, S> Deref for FrozenMap<K, V, S> {
-    type Target = HashMap<K, V, S>;
 
-    fn deref(&self) -> &Self::Target {
-        &self.map
+    /// Get the number of elements in this set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Check whether this set contains no elements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Check whether this set has more than `N` elements, and so has spilled
+    /// onto the heap.
+    #[must_use]
+    pub fn spilled(&self) -> bool {
+        self.elements.spilled()
+    }
+
+    /// Iterate over the elements of this set, in an unspecified order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.elements.iter()
     }
 }
-impl<K, V, S: BuildHasher + Default> Default for FrozenMap<K, V, S> {
+#[cfg(feature = "smallvec")]
+impl<T, const N: usize> Default for FrozenSmallSet<T, N> {
     fn default() -> Self {
         Self {
-            map: HashMap::default(),
+            elements: smallvec::SmallVec::new(),
         }
     }
 }
-impl<T, K, V, S> From<T> for FrozenMap<K, V, S>
-where
-    HashMap<K, V, S>: From<T>,
-{
-    fn from(map: T) -> Self {
+#[cfg(feature = "smallvec")]
+impl<T: Eq, const N: usize> FromIterator<T> for FrozenSmallSet<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut elements = smallvec::SmallVec::new();
+        for v in iter {
+            if !elements.contains(&v) {
+                elements.push(v);
+            }
+        }
         Self {
-            map: map.into(),
+            elements,
         }
     }
 }
-impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)>
-    for FrozenMap<K, V, S>
-{
-    fn from_iter<T>(iter: T) -> Self
-    where
-        T: IntoIterator<Item = (K, V)>,
-    {
+#[cfg(feature = "smallvec")]
+impl<T, S, const N: usize> From<FrozenSet<T, S>> for FrozenSmallSet<T, N> {
+    fn from(set: FrozenSet<T, S>) -> Self {
+        // The elements of a `FrozenSet` are already distinct.
         Self {
-            map: iter.into_iter().collect(),
+            elements: set.set.into_iter().collect(),
         }
     }
 }
-impl<K: Eq + Hash + Borrow<Q>, Q: Eq + Hash + ?Sized, V, S: BuildHasher> Index<&Q>
-    for FrozenMap<K, V, S>
+#[cfg(feature = "smallvec")]
+impl<T: Eq + Hash, S: BuildHasher + Default, const N: usize>
+    From<FrozenSmallSet<T, N>> for FrozenSet<T, S>
 {
-    type Output = V;
+    fn from(set: FrozenSmallSet<T, N>) -> Self {
+        set.elements.into_iter().collect()
+    }
+}
+#[cfg(feature = "smallvec")]
+impl<T, const N: usize> IntoIterator for FrozenSmallSet<T, N> {
+    type IntoIter = smallvec::IntoIter<[T; N]>;
+    type Item = T;
 
-    fn index(&self, key: &Q) -> &Self::Output {
-        &self.map[key]
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
     }
 }
-impl<K, V, S> IntoIterator for FrozenMap<K, V, S> {
-    type IntoIter = hash_map::IntoIter<K, V>;
-    type Item = (K, V);
+#[cfg(feature = "smallvec")]
+impl<'a, T, const N: usize> IntoIterator for &'a FrozenSmallSet<T, N> {
+    type IntoIter = std::slice::Iter<'a, T>;
+    type Item = &'a T;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.map.into_iter()
+        self.elements.iter()
     }
 }
-impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for FrozenMap<K, V, S> {
+#[cfg(feature = "smallvec")]
+impl<T: Eq, const N: usize> PartialEq for FrozenSmallSet<T, N> {
     fn eq(&self, other: &Self) -> bool {
-        self.map.eq(&other.map)
+        self.len() == other.len() && self.iter().all(|v| other.contains(v))
     }
 }
-impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for FrozenMap<K, V, S> {
+#[cfg(feature = "smallvec")]
+impl<T: Eq, const N: usize> Eq for FrozenSmallSet<T, N> {
 }
-impl<K: UnwindSafe, V: UnwindSafe, S: UnwindSafe> UnwindSafe for FrozenMap<K, V, S> {
+#[cfg(feature = "smallvec")]
+impl<T: Eq + Hash, S: BuildHasher, const N: usize> PartialEq<FrozenSet<T, S>>
+    for FrozenSmallSet<T, N>
+{
+    fn eq(&self, other: &FrozenSet<T, S>) -> bool {
+        self.len() == other.len() && self.iter().all(|v| other.contains(v))
+    }
 }
-impl<K: Hash, V: Hash, S> Hash for FrozenMap<K, V, S> {
+#[cfg(feature = "smallvec")]
+impl<T: Hash, const N: usize> Hash for FrozenSmallSet<T, N> {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        // A fairly simple hash algorithm. Probably not *great* from a
-        // collision-avoidance perspective, but it's fast, simple, and
-        // consistent.
-
-        // The overall hash is the XOR of the hashes of all the key-value pairs,
-        // which will be consistent no matter the iteration order.
-        let mut overall_hash = 0;
-        for (k, v) in &self.map {
-            let mut hasher = DefaultHasher::new();
-            k.hash(&mut hasher);
-            v.hash(&mut hasher);
-            overall_hash ^= hasher.finish();
-        }
-        overall_hash.hash(state);
+        combined_element_hash(&self.elements).hash(state);
     }
 }
 
-/// A `FrozenSet` is a wrapper around a [`HashSet`] that implements [`Hash`].
+/// An `ElementInterner` hands out shared [`Arc`]s for equal values, so that
+/// many sets containing the same large elements (such as long strings) can
+/// share one allocation per distinct element.
 ///
-/// It is a logic error to mutate any element of the set (via internal
-/// mutability) after it has been frozen.
+/// An `Arc<T>` hashes and compares equal exactly as the `T` it points to does,
+/// so a `FrozenSet<Arc<T>>` produced by [`intern_set`](Self::intern_set)
+/// behaves just like the equivalent `FrozenSet<T>`, hashes included.
 ///
-/// For convenience, `FrozenSet` implements all of [`HashSet`]'s traits, and
-/// will [`Deref`] to [`HashSet`], so you can use it as a drop-in replacement
-/// for an `&HashSet`.
+/// ```rust
+/// use std::collections::hash_map::RandomState;
+/// use std::hash::BuildHasher;
+/// use std::sync::Arc;
+///
+/// use frozenset::{ElementInterner, FrozenSet};
+///
+/// let (x, y) = ("x".to_string(), "y".to_string());
+/// let mut interner = ElementInterner::new();
+/// let a: FrozenSet<Arc<String>> = interner.intern_set([x.clone(), y.clone()]);
+/// let b: FrozenSet<Arc<String>> = interner.intern_set([y.clone()]);
+/// assert!(Arc::ptr_eq(a.get(&y).unwrap(), b.get(&y).unwrap()));
+/// assert_eq!(interner.len(), 2);
+///
+/// let again: FrozenSet<Arc<String>> = interner.intern_set([y.clone(), x.clone()]);
+/// assert_eq!(a, again);
+/// assert_ne!(a, b);
+///
+/// let plain: FrozenSet<String> = [x, y].into();
+/// let hasher = RandomState::new();
+/// assert_eq!(hasher.hash_one(&a), hasher.hash_one(&plain));
+/// ```
+#[cfg(feature = "intern")]
 #[derive(Debug, Clone)]
-pub struct FrozenSet<T, S = RandomState> {
-    set: HashSet<T, S>,
+pub struct ElementInterner<T, S = RandomState> {
+    elements: HashSet<Arc<T>, S>,
 }
-impl<T> FrozenSet<T> {
-    /// Create a new empty `FrozenSet` with the default hasher.
+#[cfg(feature = "intern")]
+impl<T> ElementInterner<T, RandomState> {
+    /// Create a new empty `ElementInterner` with the default hasher.
     #[must_use]
     pub fn new() -> Self {
-        Self {
-            set: HashSet::new(),
+        Self::default()
+    }
+}
+#[cfg(feature = "intern")]
+impl<T: Eq + Hash, S: BuildHasher> ElementInterner<T, S> {
+    /// Get the shared `Arc` for `value`, creating it if this is the first time
+    /// an equal value has been interned.
+    pub fn intern(&mut self, value: T) -> Arc<T> {
+        if let Some(existing) = self.elements.get(&value) {
+            return Arc::clone(existing);
         }
+        let value = Arc::new(value);
+        self.elements.insert(Arc::clone(&value));
+        value
     }
 
-    /// Un-freeze this `FrozenSet`, returning the underlying [`HashSet`].
+    /// Intern every element of `iter`, collecting the shared `Arc`s into a
+    /// `FrozenSet`.
+    pub fn intern_set<S2: BuildHasher + Default>(
+        &mut self,
+        iter: impl IntoIterator<Item = T>,
+    ) -> FrozenSet<Arc<T>, S2> {
+        iter.into_iter().map(|v| self.intern(v)).collect()
+    }
+
+    /// Get the number of distinct values interned so far.
     #[must_use]
-    pub fn thaw(self) -> HashSet<T> {
-        self.set
+    pub fn len(&self) -> usize {
+        self.elements.len()
     }
-}
-impl<T, S> Deref for FrozenSet<T, S> {
-    type Target = HashSet<T, S>;
 
-    fn deref(&self) -> &Self::Target {
-        &self.set
+    /// Check whether no values have been interned yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
     }
 }
-impl<T, S: Default> Default for FrozenSet<T, S> {
+#[cfg(feature = "intern")]
+impl<T, S: Default> Default for ElementInterner<T, S> {
     fn default() -> Self {
         Self {
-            set: HashSet::default(),
-        }
-    }
-}
-impl<T, F, S> From<F> for FrozenSet<T, S>
-where
-    HashSet<T, S>: From<F>,
-{
-    fn from(set: F) -> Self {
-        Self {
-            set: set.into(),
-        }
-    }
-}
-impl<T: Eq + Hash, S: BuildHasher + Default> FromIterator<T> for FrozenSet<T, S> {
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        Self {
-            set: iter.into_iter().collect(),
-        }
-    }
-}
-impl<T, S> IntoIterator for FrozenSet<T, S> {
-    type IntoIter = hash_set::IntoIter<T>;
-    type Item = T;
-
-    fn into_iter(self) -> Self::IntoIter {
-        self.set.into_iter()
-    }
-}
-impl<T: Hash + Eq, S: BuildHasher> PartialEq for FrozenSet<T, S> {
-    fn eq(&self, other: &Self) -> bool {
-        self.set.eq(&other.set)
-    }
-}
-impl<T: Hash + Eq, S: BuildHasher> Eq for FrozenSet<T, S> {
-}
-impl<T: UnwindSafe, S: UnwindSafe> UnwindSafe for FrozenSet<T, S> {
-}
-impl<T: Hash, S> Hash for FrozenSet<T, S> {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        // A fairly simple hash algorithm. Probably not *great* from a
-        // collision-avoidance perspective, but it's fast, simple, and
-        // consistent.
-
-        // The overall hash is the XOR of the hashes of all the elements, which
-        // will be consistent no matter the iteration order.
-        let mut overall_hash = 0;
-        for v in &self.set {
-            let mut hasher = DefaultHasher::new();
-            v.hash(&mut hasher);
-            overall_hash ^= hasher.finish();
+            elements: HashSet::default(),
         }
-        overall_hash.hash(state);
     }
 }
 
 #[cfg(feature = "serde")]
-impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for FrozenMap<K, V> {
-    fn serialize<S: serde::Serializer>(
+impl<K: serde::Serialize, V: serde::Serialize, S> serde::Serialize
+    for FrozenMap<K, V, S>
+{
+    fn serialize<Ser: serde::Serializer>(
         &self,
-        serializer: S,
-    ) -> Result<S::Ok, S::Error> {
+        serializer: Ser,
+    ) -> Result<Ser::Ok, Ser::Error> {
         self.map.serialize(serializer)
     }
 }
 #[cfg(feature = "serde")]
-impl<'de, K: serde::Deserialize<'de> + Hash + Eq, V: serde::Deserialize<'de>>
-    serde::Deserialize<'de> for FrozenMap<K, V>
+impl<
+        'de,
+        K: serde::Deserialize<'de> + Hash + Eq,
+        V: serde::Deserialize<'de>,
+        S: BuildHasher + Default,
+    > serde::Deserialize<'de> for FrozenMap<K, V, S>
 {
     fn deserialize<D: serde::Deserializer<'de>>(
         deserializer: D,
@@ -301,17 +3545,31 @@ impl<'de, K: serde::Deserialize<'de> + Hash + Eq, V: serde::Deserialize<'de>>
     }
 }
 #[cfg(feature = "serde")]
-impl<T: serde::Serialize> serde::Serialize for FrozenSet<T> {
-    fn serialize<S: serde::Serializer>(
+impl<T: serde::Serialize, S> serde::Serialize for FrozenSet<T, S> {
+    fn serialize<Ser: serde::Serializer>(
         &self,
-        serializer: S,
-    ) -> Result<S::Ok, S::Error> {
+        serializer: Ser,
+    ) -> Result<Ser::Ok, Ser::Error> {
         self.set.serialize(serializer)
     }
 }
+/// Sets are written as a length followed by their elements, so compact
+/// formats such as `postcard` work too, with any hasher:
+/// ```rust
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::BuildHasherDefault;
+///
+/// use frozenset::FrozenSet;
+///
+/// type Set = FrozenSet<u32, BuildHasherDefault<DefaultHasher>>;
+///
+/// let set: Set = [1, 2, 3].into_iter().collect();
+/// let bytes = postcard::to_allocvec(&set).unwrap();
+/// assert_eq!(postcard::from_bytes::<Set>(&bytes).unwrap(), set);
+/// ```
 #[cfg(feature = "serde")]
-impl<'de, T: serde::Deserialize<'de> + Hash + Eq> serde::Deserialize<'de>
-    for FrozenSet<T>
+impl<'de, T: serde::Deserialize<'de> + Hash + Eq, S: BuildHasher + Default>
+    serde::Deserialize<'de> for FrozenSet<T, S>
 {
     fn deserialize<D: serde::Deserializer<'de>>(
         deserializer: D,
@@ -321,3 +3579,256 @@ impl<'de, T: serde::Deserialize<'de> + Hash + Eq> serde::Deserialize<'de>
         })
     }
 }
+#[cfg(feature = "serde_json")]
+impl<S: BuildHasher> FrozenMap<String, serde_json::Value, S> {
+    /// Get the value for `key`, deserialized into a `T`.
+    ///
+    /// Returns `Ok(None)` if the key is missing, and `Err` if the value does
+    /// not have the right shape for a `T`.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    /// use serde_json::json;
+    ///
+    /// let config: FrozenMap<String, serde_json::Value> =
+    ///     [("port".to_string(), json!(8080))].into();
+    /// assert_eq!(config.get_as::<u16>("port").unwrap(), Some(8080));
+    /// assert_eq!(config.get_as::<u16>("host").unwrap(), None);
+    /// assert!(config.get_as::<String>("port").is_err());
+    /// ```
+    pub fn get_as<T: serde::de::DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>, serde_json::Error> {
+        self.map.get(key).map(T::deserialize).transpose()
+    }
+
+    /// Deserialize this whole map into a `T`, as if it were a JSON object.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    /// use serde::Deserialize;
+    /// use serde_json::json;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     host: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// let map: FrozenMap<String, serde_json::Value> =
+    ///     [("host".into(), json!("localhost")), ("port".into(), json!(80))].into();
+    /// let config: Config = map.deserialize_into().unwrap();
+    /// assert_eq!((config.host.as_str(), config.port), ("localhost", 80));
+    ///
+    /// let map: FrozenMap<String, serde_json::Value> = [("port".into(), json!(80))].into();
+    /// assert!(map.deserialize_into::<Config>().is_err());
+    /// ```
+    pub fn deserialize_into<T: serde::de::DeserializeOwned>(
+        &self,
+    ) -> Result<T, serde_json::Error> {
+        let entries = self.map.iter().map(|(k, v)| (k.as_str(), v));
+        T::deserialize(serde::de::value::MapDeserializer::new(entries))
+    }
+}
+#[cfg(feature = "serde_json")]
+impl<K: Ord + serde::Serialize, V: serde::Serialize, S> FrozenMap<K, V, S> {
+    /// Serialize this map as pretty-printed JSON, with its keys sorted.
+    ///
+    /// Sorting the keys (which is why `K` must be [`Ord`]) makes the output
+    /// deterministic, so it can be diffed. As with any JSON object, the keys
+    /// must serialize as strings or numbers.
+    pub fn to_pretty_json(&self) -> Result<String, serde_json::Error> {
+        let sorted: std::collections::BTreeMap<_, _> = self.map.iter().collect();
+        serde_json::to_string_pretty(&sorted)
+    }
+}
+#[cfg(feature = "serde_json")]
+impl<T: Ord + serde::Serialize, S> FrozenSet<T, S> {
+    /// Serialize this set as a pretty-printed JSON array, with its elements
+    /// sorted.
+    ///
+    /// Sorting the elements (which is why `T` must be [`Ord`]) makes the output
+    /// deterministic, so it can be diffed.
+    pub fn to_pretty_json(&self) -> Result<String, serde_json::Error> {
+        let sorted: BTreeSet<_> = self.set.iter().collect();
+        serde_json::to_string_pretty(&sorted)
+    }
+}
+#[cfg(feature = "petgraph")]
+impl<N, S> FrozenSet<N, S> {
+    /// Create a new `FrozenSet` of the identifiers of every node in `graph`.
+    ///
+    /// As node identifiers are usually small and [`Copy`], sets of them make
+    /// good keys for memoisation tables, such as in dynamic programming over
+    /// subsets of nodes.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenSet;
+    /// use petgraph::graph::{NodeIndex, UnGraph};
+    ///
+    /// let graph = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (1, 3)]);
+    /// let nodes: FrozenSet<NodeIndex> = FrozenSet::from_graph_nodes(&graph);
+    /// assert_eq!(nodes.len(), 4);
+    ///
+    /// let hub = NodeIndex::new(1);
+    /// let neighbours: FrozenSet<NodeIndex> = FrozenSet::neighbors_frozen(&graph, hub);
+    /// assert_eq!(neighbours, [0, 2, 3].map(NodeIndex::new).into());
+    /// assert!(!neighbours.contains(&hub));
+    /// ```
+    #[must_use]
+    pub fn from_graph_nodes<G>(graph: G) -> Self
+    where
+        G: petgraph::visit::IntoNodeIdentifiers<NodeId = N>,
+        N: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        graph.node_identifiers().collect()
+    }
+
+    /// Create a new `FrozenSet` of the neighbours of `node` in `graph`.
+    ///
+    /// For directed graphs, this follows [`IntoNeighbors`]'s convention of
+    /// only including the targets of outgoing edges.
+    ///
+    /// [`IntoNeighbors`]: petgraph::visit::IntoNeighbors
+    #[must_use]
+    pub fn neighbors_frozen<G>(graph: G, node: N) -> Self
+    where
+        G: petgraph::visit::IntoNeighbors<NodeId = N>,
+        N: Eq + Hash,
+        S: BuildHasher + Default,
+    {
+        graph.neighbors(node).collect()
+    }
+}
+#[cfg(feature = "regex")]
+impl<V, S: BuildHasher> FrozenMap<String, V, S> {
+    /// Get the entries of this map whose keys match `re`.
+    ///
+    /// As with [`Regex::is_match`](regex::Regex::is_match), the pattern may
+    /// match anywhere in a key; anchor it with `^` and `$` to match whole keys.
+    ///
+    /// ```rust
+    /// use frozenset::FrozenMap;
+    /// use regex::Regex;
+    ///
+    /// let config: FrozenMap<String, i32> =
+    ///     [("log.level".into(), 1), ("log.file".into(), 2), ("port".into(), 3)].into();
+    /// let logging = config.filter_keys_regex(&Regex::new(r"^log\.").unwrap());
+    /// assert_eq!(logging.len(), 2);
+    /// assert!(!logging.contains_key("port"));
+    /// ```
+    #[must_use]
+    pub fn filter_keys_regex(&self, re: &regex::Regex) -> Self
+    where
+        V: Clone,
+        S: Default,
+    {
+        self.map
+            .iter()
+            .filter(|(k, _)| re.is_match(k))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    /// Get the keys of this map which match `re`.
+    #[must_use]
+    pub fn keys_matching(&self, re: &regex::Regex) -> FrozenSet<String, S>
+    where
+        S: Default,
+    {
+        self.map
+            .keys()
+            .filter(|k| re.is_match(k))
+            .cloned()
+            .collect()
+    }
+}
+/// `FrozenMap` and `FrozenSet` are encoded in the same way as the `HashMap`
+/// and `HashSet` they wrap:
+/// ```rust
+/// use frozenset::{FrozenMap, FrozenSet};
+/// use speedy::{LittleEndian, Readable, Writable};
+///
+/// let set: FrozenSet<u32> = [1, 2, 3].into();
+/// let bytes = set.write_to_vec_with_ctx(LittleEndian::default()).unwrap();
+/// let read = FrozenSet::<u32>::read_from_buffer_with_ctx(LittleEndian::default(), &bytes);
+/// assert_eq!(read.unwrap(), set);
+///
+/// let map: FrozenMap<String, u32> = [("a".to_string(), 1), ("b".to_string(), 2)].into();
+/// let bytes = map.write_to_vec_with_ctx(LittleEndian::default()).unwrap();
+/// let read = FrozenMap::<String, u32>::read_from_buffer_with_ctx(LittleEndian::default(), &bytes);
+/// assert_eq!(read.unwrap(), map);
+/// ```
+#[cfg(feature = "speedy")]
+impl<C: speedy::Context, K: speedy::Writable<C>, V: speedy::Writable<C>, S>
+    speedy::Writable<C> for FrozenMap<K, V, S>
+{
+    fn write_to<W: ?Sized + speedy::Writer<C>>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), C::Error> {
+        self.map.write_to(writer)
+    }
+
+    fn bytes_needed(&self) -> Result<usize, C::Error> {
+        speedy::Writable::<C>::bytes_needed(&self.map)
+    }
+}
+#[cfg(feature = "speedy")]
+impl<
+        'a,
+        C: speedy::Context,
+        K: speedy::Readable<'a, C> + Eq + Hash,
+        V: speedy::Readable<'a, C>,
+        S: BuildHasher + Default,
+    > speedy::Readable<'a, C> for FrozenMap<K, V, S>
+{
+    fn read_from<R: speedy::Reader<'a, C>>(
+        reader: &mut R,
+    ) -> Result<Self, C::Error> {
+        Ok(Self {
+            map: HashMap::read_from(reader)?,
+        })
+    }
+
+    fn minimum_bytes_needed() -> usize {
+        <HashMap<K, V, S> as speedy::Readable<'a, C>>::minimum_bytes_needed()
+    }
+}
+#[cfg(feature = "speedy")]
+impl<C: speedy::Context, T: speedy::Writable<C>, S> speedy::Writable<C>
+    for FrozenSet<T, S>
+{
+    fn write_to<W: ?Sized + speedy::Writer<C>>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), C::Error> {
+        self.set.write_to(writer)
+    }
+
+    fn bytes_needed(&self) -> Result<usize, C::Error> {
+        speedy::Writable::<C>::bytes_needed(&self.set)
+    }
+}
+#[cfg(feature = "speedy")]
+impl<
+        'a,
+        C: speedy::Context,
+        T: speedy::Readable<'a, C> + Eq + Hash,
+        S: BuildHasher + Default,
+    > speedy::Readable<'a, C> for FrozenSet<T, S>
+{
+    fn read_from<R: speedy::Reader<'a, C>>(
+        reader: &mut R,
+    ) -> Result<Self, C::Error> {
+        Ok(Self {
+            set: HashSet::read_from(reader)?,
+        })
+    }
+
+    fn minimum_bytes_needed() -> usize {
+        <HashSet<T, S> as speedy::Readable<'a, C>>::minimum_bytes_needed()
+    }
+}